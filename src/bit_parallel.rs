@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::lcs::{common_prefix, common_suffix};
+
+// Above this many tokens in the shorter input, `lcs_len` is slower than
+// Myers' algorithm unless the inputs differ a lot.
+pub const MAX_LEN: usize = 4096;
+
+// counts the tokens of the longest common subsequence of two token sequences
+//
+// This algorithm is based on
+// Heikki Hyyrö, "Bit-Parallel LCS-length Computation Revisited" (2004).
+// Every bit of `v` is a column of the dynamic programming table of the
+// shorter input, and a whole row is computed with a few word operations,
+// taking O(N * M / 64) time regardless of how much the inputs differ.
+pub fn lcs_len<T: Eq + Hash>(orig: &[T], edit: &[T]) -> usize {
+    let prefix = common_prefix(orig, edit);
+    let (orig, edit) = (&orig[prefix..], &edit[prefix..]);
+    let suffix = common_suffix(orig, edit);
+    let (orig, edit) = (&orig[..orig.len() - suffix], &edit[..edit.len() - suffix]);
+    let (short, long) = if orig.len() <= edit.len() {
+        (orig, edit)
+    } else {
+        (edit, orig)
+    };
+
+    let words = short.len().div_ceil(64);
+    // token -> bits of the positions it occurs at in `short`
+    let mut positions: HashMap<&T, Vec<u64>> = HashMap::new();
+    for (i, token) in short.iter().enumerate() {
+        positions.entry(token).or_insert_with(|| vec![0; words])[i / 64] |= 1 << (i % 64);
+    }
+
+    let mut v = vec![u64::MAX; words];
+    for token in long {
+        let Some(matches) = positions.get(token) else {
+            continue;
+        };
+        let mut carry = false;
+        for (v, &matches) in v.iter_mut().zip(matches) {
+            let u = *v & matches;
+            let (sum, overflow) = v.overflowing_add(u);
+            let (sum, carried) = sum.overflowing_add(u64::from(carry));
+            carry = overflow || carried;
+            *v = sum | (*v & !u);
+        }
+    }
+
+    // every column where the row dropped a one is part of the subsequence
+    let common: usize = v
+        .iter()
+        .enumerate()
+        .map(|(word, &v)| {
+            let bits = (short.len() - 64 * word).min(64);
+            (!v & (u64::MAX >> (64 - bits))).count_ones() as usize
+        })
+        .sum();
+    prefix + common + suffix
+}
+
+#[test]
+fn test_lcs_len() {
+    let chars = |s: &str| s.chars().collect::<Vec<_>>();
+    assert_eq!(lcs_len(&chars("test"), &chars("tost")), 3);
+    assert_eq!(lcs_len(&chars("abcabba"), &chars("cbabac")), 4);
+    assert_eq!(lcs_len(&chars(""), &chars("abc")), 0);
+    assert_eq!(lcs_len(&chars("abc"), &chars("abc")), 3);
+}
@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use crate::DiffOptions;
+use crate::lcs::{bytesplit, lcs};
+use crate::slice::{SliceDifference, group};
+
+/// Calculates the differences between two byte strings, which don't need to
+/// be valid UTF-8. The third parameter specifies how to split them, leading
+/// to a more or less exact comparison.
+///
+/// Common splits are `b""` for byte-level, `b" "` for word-level and `b"\n"` for line-level.
+///
+/// Like the `Difference`s of `Changeset::new`, every difference is a part of
+/// one of the inputs, including the splits between its tokens.
+///
+/// # Examples
+///
+/// ```
+/// use difference_rs::{SliceDifference, diff_bytes};
+///
+/// let diffs = diff_bytes(b"GET /a\r\nHost: \xff", b"GET /b\r\nHost: \xff", b"\r\n");
+///
+/// assert_eq!(diffs, vec![
+///     SliceDifference::Rem(&b"GET /a"[..]),
+///     SliceDifference::Add(b"GET /b"),
+///     SliceDifference::Same(b"Host: \xff"),
+/// ]);
+/// ```
+#[must_use]
+pub fn diff_bytes<'a>(
+    orig: &'a [u8],
+    edit: &'a [u8],
+    split: &[u8],
+) -> Vec<SliceDifference<'a, u8>> {
+    let a = bytesplit(orig, split);
+    let b = bytesplit(edit, split);
+    let common = lcs(&tokens(orig, &a), &tokens(edit, &b), &DiffOptions::new());
+    group(&a, &b, &common)
+        .into_iter()
+        .map(|diff| match diff {
+            SliceDifference::Same(ranges) => SliceDifference::Same(span(orig, ranges)),
+            SliceDifference::Add(ranges) => SliceDifference::Add(span(edit, ranges)),
+            SliceDifference::Rem(ranges) => SliceDifference::Rem(span(orig, ranges)),
+        })
+        .collect()
+}
+
+// the bytes of every token of `s`
+fn tokens<'a>(s: &'a [u8], ranges: &[Range<usize>]) -> Vec<&'a [u8]> {
+    ranges.iter().map(|range| &s[range.clone()]).collect()
+}
+
+// the bytes of `s` from the first to the last of the consecutive `tokens`
+fn span<'a>(s: &'a [u8], tokens: &[Range<usize>]) -> &'a [u8] {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => &s[first.start..last.end],
+        _ => &[],
+    }
+}
+
+#[test]
+fn test_diff_bytes() {
+    assert_eq!(
+        diff_bytes(b"te\xe9st", b"te\xe8st", b""),
+        vec![
+            SliceDifference::Same(&b"te"[..]),
+            SliceDifference::Rem(b"\xe9"),
+            SliceDifference::Add(b"\xe8"),
+            SliceDifference::Same(b"st"),
+        ]
+    );
+    assert_eq!(
+        diff_bytes(b"a\nb\nc", b"a\nc", b"\n"),
+        vec![
+            SliceDifference::Same(&b"a"[..]),
+            SliceDifference::Rem(b"b"),
+            SliceDifference::Same(b"c"),
+        ]
+    );
+    assert_eq!(
+        diff_bytes(b"a||b||c", b"a||x||y||c", b"||"),
+        vec![
+            SliceDifference::Same(&b"a"[..]),
+            SliceDifference::Rem(b"b"),
+            SliceDifference::Add(b"x||y"),
+            SliceDifference::Same(b"c"),
+        ]
+    );
+    assert_eq!(
+        diff_bytes(b"", b"a", b"\n"),
+        vec![SliceDifference::Add(&b"a"[..])]
+    );
+}
+
+#[test]
+fn test_diff_bytes_like_strings() {
+    let text1 = "Roses are red, violets are blue,\nI wrote this library,\njust for you.";
+    let text2 = "Roses are red, violets are blue,\nI wrote this documentation,\njust for you.";
+
+    for split in ["", " ", "\n"] {
+        let diffs = diff_bytes(text1.as_bytes(), text2.as_bytes(), split.as_bytes())
+            .into_iter()
+            .map(|diff| {
+                let text = String::from_utf8(diff.as_slice().to_vec()).unwrap();
+                match diff {
+                    SliceDifference::Same(_) => crate::Difference::Same(text),
+                    SliceDifference::Add(_) => crate::Difference::Add(text),
+                    SliceDifference::Rem(_) => crate::Difference::Rem(text),
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(diffs, crate::Changeset::new(text1, text2, split).diffs);
+    }
+}
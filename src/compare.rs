@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "unicode")]
+use unicode_normalization::{UnicodeNormalization, is_nfc, is_nfd, is_nfkc, is_nfkd};
+
+use crate::DiffOptions;
+use crate::lcs::{Snake, lcs};
+
+/// How whitespace is compared, like the whitespace options of GNU diff
+///
+/// The differences still contain the original text, including whitespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Whitespace {
+    /// Whitespace is compared like any other text
+    #[default]
+    Exact,
+    /// Ignores changes in the amount of whitespace, like `diff -b`. Runs of
+    /// whitespace compare equal to a single space, and whitespace at the end
+    /// of a token is ignored.
+    IgnoreAmount,
+    /// Ignores all whitespace, like `diff -w`
+    IgnoreAll,
+    /// Ignores whitespace at the end of a token, like `diff -Z`
+    IgnoreTrailing,
+}
+
+/// The Unicode normalization forms tokens can be compared in
+///
+/// See [UAX #15](https://www.unicode.org/reports/tr15/) for the forms.
+#[cfg(feature = "unicode")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Normalization {
+    /// Tokens are compared as they are
+    #[default]
+    None,
+    /// Canonical composition, where "e\u{301}" is "\u{e9}"
+    Nfc,
+    /// Canonical decomposition, where "\u{e9}" is "e\u{301}"
+    Nfd,
+    /// Compatibility composition, like `Nfc` but where "\u{fb01}" is "fi"
+    Nfkc,
+    /// Compatibility decomposition, like `Nfd` but where "\u{fb01}" is "fi"
+    Nfkd,
+}
+
+/// How the line endings of `DiffOptions::lines` are compared
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEndings {
+    /// Line endings are part of the line they end, so a line that only
+    /// changed its ending is removed and added as a whole
+    #[default]
+    Compare,
+    /// Line endings are ignored, so lines that only differ in their endings
+    /// are the same
+    Ignore,
+    /// Line endings are tokens of their own, so changes to them are reported
+    /// apart from the lines they end
+    Report,
+}
+
+// finds the runs of tokens `a` and `b` have in common, comparing them as
+// configured by `options`
+pub fn common(a: &[&str], b: &[&str], options: &DiffOptions) -> Vec<Snake> {
+    if exact(options) {
+        return lcs(a, b, options);
+    }
+    lcs(&keys(a, options), &keys(b, options), options)
+}
+
+// checks whether tokens are compared as they are
+fn exact(options: &DiffOptions) -> bool {
+    #[cfg(feature = "unicode")]
+    if options.ignore_case || options.normalization != Normalization::None {
+        return false;
+    }
+    options.whitespace == Whitespace::Exact && options.line_endings != LineEndings::Ignore
+}
+
+// the text every one of `tokens` is compared by
+fn keys<'a>(tokens: &[&'a str], options: &DiffOptions) -> Vec<Cow<'a, str>> {
+    tokens.iter().map(|token| key(token, options)).collect()
+}
+
+// the text a token is compared by
+fn key<'a>(token: &'a str, options: &DiffOptions) -> Cow<'a, str> {
+    let token = if options.line_endings == LineEndings::Ignore {
+        token
+            .strip_suffix("\r\n")
+            .or_else(|| token.strip_suffix(['\n', '\r']))
+            .unwrap_or(token)
+    } else {
+        token
+    };
+    let key = whitespace(token, options.whitespace);
+    #[cfg(feature = "unicode")]
+    {
+        let key = normalize(key, options.normalization);
+        if options.ignore_case {
+            // folding may denormalize the text again
+            let folded = caseless::default_case_fold_str(&key);
+            return normalize(Cow::Owned(folded), options.normalization);
+        }
+        key
+    }
+    #[cfg(not(feature = "unicode"))]
+    key
+}
+
+// the text in the normalization form `form`
+#[cfg(feature = "unicode")]
+fn normalize(text: Cow<'_, str>, form: Normalization) -> Cow<'_, str> {
+    match form {
+        Normalization::Nfc if !is_nfc(&text) => Cow::Owned(text.nfc().collect()),
+        Normalization::Nfd if !is_nfd(&text) => Cow::Owned(text.nfd().collect()),
+        Normalization::Nfkc if !is_nfkc(&text) => Cow::Owned(text.nfkc().collect()),
+        Normalization::Nfkd if !is_nfkd(&text) => Cow::Owned(text.nfkd().collect()),
+        _ => text,
+    }
+}
+
+// the text a token is compared by, ignoring `whitespace`
+fn whitespace(token: &str, whitespace: Whitespace) -> Cow<'_, str> {
+    match whitespace {
+        Whitespace::Exact => Cow::Borrowed(token),
+        Whitespace::IgnoreTrailing => Cow::Borrowed(token.trim_end()),
+        Whitespace::IgnoreAmount => {
+            let token = token.trim_end();
+            if !token.contains(|c: char| c.is_whitespace() && c != ' ') && !token.contains("  ") {
+                return Cow::Borrowed(token);
+            }
+            let mut key = String::with_capacity(token.len());
+            let mut space = false;
+            for c in token.chars() {
+                if c.is_whitespace() {
+                    space = true;
+                } else {
+                    if space {
+                        key.push(' ');
+                        space = false;
+                    }
+                    key.push(c);
+                }
+            }
+            Cow::Owned(key)
+        }
+        Whitespace::IgnoreAll => {
+            if token.contains(char::is_whitespace) {
+                Cow::Owned(token.chars().filter(|c| !c.is_whitespace()).collect())
+            } else {
+                Cow::Borrowed(token)
+            }
+        }
+    }
+}
+
+// checks whether a token is empty or only whitespace, see
+// `DiffOptions::ignore_blank_lines`
+pub fn is_blank(token: &str) -> bool {
+    token.trim().is_empty()
+}
+
+#[test]
+fn test_key() {
+    let key = |whitespace, token| key(token, &DiffOptions::new().whitespace(whitespace));
+
+    assert_eq!(key(Whitespace::Exact, " a  b "), " a  b ");
+    assert_eq!(key(Whitespace::IgnoreTrailing, " a  b \t"), " a  b");
+    assert_eq!(key(Whitespace::IgnoreAmount, "a b"), "a b");
+    assert_eq!(key(Whitespace::IgnoreAmount, "\t a \t b \r"), " a b");
+    assert_eq!(key(Whitespace::IgnoreAmount, "a\tb"), "a b");
+    assert_eq!(key(Whitespace::IgnoreAll, " a \t b\r"), "ab");
+    assert!(matches!(key(Whitespace::IgnoreAll, "ab"), Cow::Borrowed(_)));
+
+    assert!(is_blank("") && is_blank(" \t\r") && !is_blank(" a "));
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_key_ignore_case() {
+    let options = DiffOptions::new().ignore_case(true);
+    assert_eq!(key("Straße", &options), key("STRASSE", &options));
+    assert_eq!(key("ΣΑΣ", &options), key("σας", &options));
+    assert_ne!(key("a", &options), key("á", &options));
+
+    let options = options.whitespace(Whitespace::IgnoreAll);
+    assert_eq!(key(" SELECT\t", &options), "select");
+
+    let words = DiffOptions::new().split(" ").ignore_case(true);
+    assert_eq!(
+        crate::Changeset::with_options("groß", "GROSS", &words).distance,
+        0
+    );
+    let chars = DiffOptions::new().ignore_case(true);
+    assert_eq!(
+        crate::Changeset::with_options("groß", "GROSS", &chars).distance,
+        3
+    );
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_key_normalization() {
+    let normalized = |form, token| key(token, &DiffOptions::new().normalization(form));
+
+    assert_ne!(normalized(Normalization::None, "\u{e9}"), "e\u{301}");
+    assert_eq!(normalized(Normalization::Nfc, "e\u{301}"), "\u{e9}");
+    assert_eq!(normalized(Normalization::Nfd, "\u{e9}"), "e\u{301}");
+    assert_eq!(normalized(Normalization::Nfc, "\u{fb01}"), "\u{fb01}");
+    assert_eq!(normalized(Normalization::Nfkc, "\u{fb01}"), "fi");
+    assert_eq!(
+        normalized(Normalization::Nfkd, "\u{1e9b}\u{323}"),
+        "s\u{323}\u{307}"
+    );
+    assert!(matches!(
+        normalized(Normalization::Nfc, "abc"),
+        Cow::Borrowed(_)
+    ));
+
+    let options = DiffOptions::new()
+        .normalization(Normalization::Nfc)
+        .ignore_case(true);
+    assert_eq!(key("\u{c9}", &options), key("e\u{301}", &options));
+}
+
+#[test]
+fn test_key_line_endings() {
+    let options = DiffOptions::new().lines(LineEndings::Ignore);
+    assert_eq!(key("a\r\n", &options), "a");
+    assert_eq!(key("a\n", &options), "a");
+    assert_eq!(key("a\r", &options), "a");
+    assert_eq!(key("a \n\n", &options), "a \n");
+
+    let options = DiffOptions::new().lines(LineEndings::Compare);
+    assert_eq!(key("a\r\n", &options), "a\r\n");
+}
+
+#[test]
+fn test_common() {
+    let a = ["if x {", "    y();", "}"];
+    let b = ["if x  {", "\ty();  ", "}"];
+    let same = |whitespace| {
+        common(&a, &b, &DiffOptions::new().whitespace(whitespace))
+            .iter()
+            .map(|snake| snake.len)
+            .sum::<usize>()
+    };
+
+    assert_eq!(same(Whitespace::Exact), 1);
+    assert_eq!(same(Whitespace::IgnoreTrailing), 1);
+    assert_eq!(same(Whitespace::IgnoreAmount), 3);
+    assert_eq!(same(Whitespace::IgnoreAll), 3);
+}
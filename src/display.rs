@@ -1,4 +1,4 @@
-use crate::ChangesetMulti;
+use crate::{ChangesetMulti, ChangesetRef, DifferenceRef};
 
 use super::{Changeset, Difference};
 use std::{char::REPLACEMENT_CHARACTER, fmt};
@@ -22,6 +22,25 @@ impl fmt::Display for Changeset {
     }
 }
 
+impl fmt::Display for ChangesetRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for d in &self.diffs {
+            match *d {
+                DifferenceRef::Same(x) => {
+                    write!(f, "{}{}", x, self.split)?;
+                }
+                DifferenceRef::Add(x) => {
+                    write!(f, "\x1b[92m{}\x1b[0m{}", x, self.split)?;
+                }
+                DifferenceRef::Rem(x) => {
+                    write!(f, "\x1b[91m{}\x1b[0m{}", x, self.split)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for ChangesetMulti {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut orig_counter = 0;
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::lcs::{Limits, Snake, diff_parts, expired, myers, parallel, push};
+
+// Tokens that occur more often than this in the original are never used as
+// anchors, like git's `MAX_CHAIN_LENGTH`.
+const MAX_OCCURRENCES: usize = 64;
+
+// finds the runs of tokens two sequences have in common, anchoring on the
+// tokens that occur the least in the original
+//
+// This algorithm is based on git's histogram diff (`xdiff/xhistogram.c`),
+// itself an extension of patience diff: the longest common region around
+// the rarest tokens is matched first, then the parts before and after it
+// are diffed the same way. Parts where every common token is too frequent
+// fall back to Myers' algorithm.
+pub fn diff<T: Eq + Hash + Sync>(orig: &[T], edit: &[T], limits: Limits) -> Vec<Snake> {
+    let index = Index::new(orig);
+    let mut snakes = Vec::new();
+    histogram(
+        orig,
+        edit,
+        &index,
+        0..orig.len(),
+        0..edit.len(),
+        limits,
+        &mut snakes,
+    );
+    snakes
+}
+
+// where every token occurs in the original, built once for all of its parts
+struct Index<'a, T> {
+    positions: HashMap<&'a T, Vec<usize>>,
+}
+
+impl<'a, T: Eq + Hash> Index<'a, T> {
+    fn new(orig: &'a [T]) -> Index<'a, T> {
+        let mut positions: HashMap<&T, Vec<usize>> = HashMap::new();
+        for (i, token) in orig.iter().enumerate() {
+            positions.entry(token).or_default().push(i);
+        }
+        Index { positions }
+    }
+
+    // the positions of `token` in the part `a` of the original, in order
+    fn positions(&self, token: &T, a: &Range<usize>) -> &[usize] {
+        let positions = self.positions.get(token).map_or(&[][..], Vec::as_slice);
+        let start = positions.partition_point(|&i| i < a.start);
+        let end = positions.partition_point(|&i| i < a.end);
+        &positions[start..end]
+    }
+}
+
+// appends the snakes of the part `a` of `orig` and `b` of `edit` to `snakes`
+//
+// The parts around every region are diffed in a loop rather than recursively,
+// since inputs where the regions interleave split into about as many parts as
+// they have tokens.
+fn histogram<T: Eq + Hash + Sync>(
+    orig: &[T],
+    edit: &[T],
+    index: &Index<'_, T>,
+    a: Range<usize>,
+    b: Range<usize>,
+    limits: Limits,
+    snakes: &mut Vec<Snake>,
+) {
+    // the parts left to diff, the next one last, each followed by the tokens
+    // matched after it
+    let end = Snake {
+        a: a.end,
+        b: b.end,
+        len: 0,
+    };
+    let mut parts = vec![(a, b, end)];
+    while let Some((a, b, matched)) = parts.pop() {
+        if a.is_empty() || b.is_empty() || expired(limits.deadline) {
+            push(snakes, matched);
+            continue;
+        }
+
+        match rarest_region(orig, edit, index, &a, &b) {
+            Region::Found(region) => {
+                let (x, y) = (region.a + region.len, region.b + region.len);
+                let sides = [
+                    (a.start..region.a, b.start..region.b, region),
+                    (x..a.end, y..b.end, matched),
+                ];
+                let smaller = sides
+                    .iter()
+                    .map(|(side_a, side_b, _)| side_a.len() + side_b.len())
+                    .min()
+                    .unwrap_or_default();
+                if parallel(smaller, limits) {
+                    // only diffing two large sides recurses, which can't
+                    // happen often
+                    diff_parts(
+                        &sides,
+                        a.len() + b.len(),
+                        limits,
+                        snakes,
+                        |(side_a, side_b, matched), snakes| {
+                            histogram(
+                                orig,
+                                edit,
+                                index,
+                                side_a.clone(),
+                                side_b.clone(),
+                                limits,
+                                snakes,
+                            );
+                            push(snakes, *matched);
+                        },
+                    );
+                } else {
+                    let [before, after] = sides;
+                    parts.extend([after, before]);
+                }
+            }
+            Region::TooFrequent => {
+                for snake in myers(&orig[a.clone()], &edit[b.clone()], limits) {
+                    push(
+                        snakes,
+                        Snake {
+                            a: a.start + snake.a,
+                            b: b.start + snake.b,
+                            len: snake.len,
+                        },
+                    );
+                }
+                push(snakes, matched);
+            }
+            Region::Disjoint => push(snakes, matched),
+        }
+    }
+}
+
+// the outcome of looking for the region to anchor on
+enum Region {
+    Found(Snake),
+    // every common token occurs more than `MAX_OCCURRENCES` times
+    TooFrequent,
+    // the inputs have no token in common
+    Disjoint,
+}
+
+// finds the common region of the part `a` of `orig` and `b` of `edit` whose
+// least frequent token occurs the fewest times in that part of `orig`,
+// preferring the longest of them
+fn rarest_region<T: Eq + Hash>(
+    orig: &[T],
+    edit: &[T],
+    index: &Index<'_, T>,
+    a: &Range<usize>,
+    b: &Range<usize>,
+) -> Region {
+    let occurrences = |token: &T| index.positions(token, a).len();
+
+    let mut best: Option<Snake> = None;
+    let mut best_occurrences = MAX_OCCURRENCES + 1;
+    let mut disjoint = true;
+    let mut j = b.start;
+    while j < b.end {
+        let mut next = j + 1;
+        let candidates = index.positions(&edit[j], a);
+        disjoint &= candidates.is_empty();
+        if candidates.len() <= best_occurrences {
+            // occurrences inside a region found from an earlier one would
+            // only find that region again
+            let mut skip_until = 0;
+            for &i in candidates {
+                if i < skip_until {
+                    continue;
+                }
+                let mut rarest = candidates.len();
+                let (mut start_a, mut start_b) = (i, j);
+                while start_a > a.start
+                    && start_b > b.start
+                    && orig[start_a - 1] == edit[start_b - 1]
+                {
+                    start_a -= 1;
+                    start_b -= 1;
+                    rarest = rarest.min(occurrences(&orig[start_a]));
+                }
+                let (mut end_a, mut end_b) = (i + 1, j + 1);
+                while end_a < a.end && end_b < b.end && orig[end_a] == edit[end_b] {
+                    rarest = rarest.min(occurrences(&orig[end_a]));
+                    end_a += 1;
+                    end_b += 1;
+                }
+
+                next = next.max(end_b);
+                skip_until = end_a;
+                let len = end_a - start_a;
+                if best.is_none_or(|best| best.len < len) || rarest < best_occurrences {
+                    best = Some(Snake {
+                        a: start_a,
+                        b: start_b,
+                        len,
+                    });
+                    best_occurrences = rarest;
+                }
+            }
+        }
+        j = next;
+    }
+
+    match best {
+        Some(region) => Region::Found(region),
+        None if disjoint => Region::Disjoint,
+        None => Region::TooFrequent,
+    }
+}
+
+#[cfg(test)]
+use crate::DiffOptions;
+
+#[test]
+fn test_histogram() {
+    let limits = Limits::from(&DiffOptions::new());
+    // "x" occurs once, so it anchors before the more frequent "a" and "b"
+    assert_eq!(
+        diff(&["a", "b", "x", "a", "b"], &["x", "a", "b", "a"], limits),
+        vec![Snake { a: 2, b: 0, len: 3 }]
+    );
+
+    assert_eq!(diff::<u8>(&[], &[1], limits), vec![]);
+    assert_eq!(
+        diff(&[1, 2, 3], &[1, 2, 3], limits),
+        vec![Snake { a: 0, b: 0, len: 3 }]
+    );
+
+    // without any anchor the parts are still diffed with Myers
+    let orig = [0, 1].repeat(MAX_OCCURRENCES + 1);
+    let edit = [1, 0].repeat(MAX_OCCURRENCES + 1);
+    assert_eq!(
+        diff(&orig, &edit, limits),
+        vec![Snake {
+            a: 1,
+            b: 0,
+            len: orig.len() - 1
+        }]
+    );
+}
+
+#[test]
+fn test_histogram_interleaved() {
+    // every region is followed by a part with all but two of the remaining
+    // tokens, which used to be diffed recursively, once per region
+    let orig = (0..4000).collect::<Vec<u32>>();
+    let edit = orig.iter().map(|i| i ^ 1).collect::<Vec<_>>();
+    let limits = Limits::from(&DiffOptions::new());
+
+    let snakes = diff(&orig, &edit, limits);
+    assert_eq!(snakes.len(), 2000);
+    assert_eq!(snakes[0], Snake { a: 1, b: 0, len: 1 });
+    assert!(snakes.iter().all(|snake| orig[snake.a] == edit[snake.b]));
+}
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::iter::{FusedIterator, Peekable};
+
+use crate::lcs::{Limits, Snake, common_prefix, common_suffix, intern, myers, slide, split_tokens};
+use crate::merge::span;
+use crate::myers::Bisection;
+use crate::{DiffOptions, Difference, DifferenceRef};
+
+/// An iterator over the differences between two strings, created by
+/// `Changeset::iter_diff`
+///
+/// It yields the same `Difference`s as the `diffs` of `Changeset::new`, in
+/// order. For large inputs, every one of them is yielded as soon as it is
+/// found, before the rest of the inputs is compared.
+pub struct DiffIter<'a> {
+    orig: &'a str,
+    edit: &'a str,
+    a: Vec<&'a str>,
+    b: Vec<&'a str>,
+    split: &'a str,
+    snakes: Peekable<Box<dyn Iterator<Item = Snake> + Send>>,
+    // the tokens before these were yielded already
+    i: usize,
+    j: usize,
+    // the differences found but not yielded yet
+    found: VecDeque<DifferenceRef<'a>>,
+    done: bool,
+}
+
+impl<'a> DiffIter<'a> {
+    pub(crate) fn new(orig: &'a str, edit: &'a str, split: &'a str) -> DiffIter<'a> {
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+
+        // like `lcs`, with Myers' algorithm switching to its linear space
+        // variant, which is the one that can be searched lazily
+        let limits = Limits::from(&DiffOptions::new());
+        let (ids_a, ids_b) = intern(&a, &b);
+        let prefix = common_prefix(&ids_a, &ids_b);
+        let suffix = common_suffix(&ids_a[prefix..], &ids_b[prefix..]);
+        let (end_a, end_b) = (ids_a.len() - suffix, ids_b.len() - suffix);
+        let (rest_a, rest_b) = (&ids_a[prefix..end_a], &ids_b[prefix..end_b]);
+        let rest: Box<dyn Iterator<Item = Snake> + Send> =
+            if rest_a.len() + rest_b.len() > limits.linear_space_threshold {
+                Box::new(Bisection::new(rest_a.to_vec(), rest_b.to_vec()))
+            } else {
+                Box::new(myers(rest_a, rest_b, limits).into_iter())
+            };
+        let first = Snake {
+            a: 0,
+            b: 0,
+            len: prefix,
+        };
+        let rest = rest.map(move |snake| Snake {
+            a: prefix + snake.a,
+            b: prefix + snake.b,
+            len: snake.len,
+        });
+        let snakes: Box<dyn Iterator<Item = Snake> + Send> = Box::new(Trimmed {
+            snakes: Box::new(std::iter::once(first).chain(rest)),
+            last: (0, 0),
+            suffix: Some(Snake {
+                a: end_a,
+                b: end_b,
+                len: suffix,
+            }),
+            ids_a,
+            ids_b,
+        });
+
+        DiffIter {
+            orig,
+            edit,
+            a,
+            b,
+            split,
+            snakes: snakes.peekable(),
+            i: 0,
+            j: 0,
+            found: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    // finds the differences up to the end of the next run of common tokens
+    fn find(&mut self) {
+        let snake = if let Some(mut snake) = self.snakes.next() {
+            while let Some(next) = self
+                .snakes
+                .next_if(|next| snake.a + snake.len == next.a && snake.b + snake.len == next.b)
+            {
+                snake.len += next.len;
+            }
+            snake
+        } else {
+            self.done = true;
+            Snake {
+                a: self.a.len(),
+                b: self.b.len(),
+                len: 0,
+            }
+        };
+
+        if self.i < snake.a {
+            let removed = span(self.orig, &self.a[self.i..snake.a]);
+            self.found.push_back(DifferenceRef::Rem(removed));
+        }
+        if self.j < snake.b {
+            let added = span(self.edit, &self.b[self.j..snake.b]);
+            self.found.push_back(DifferenceRef::Add(added));
+        }
+        if snake.len > 0 {
+            let joined = span(self.orig, &self.a[snake.a..snake.a + snake.len]);
+            if !self.split.is_empty() || !joined.is_empty() {
+                self.found.push_back(DifferenceRef::Same(joined));
+            }
+        }
+        self.i = snake.a + snake.len;
+        self.j = snake.b + snake.len;
+    }
+}
+
+// the snakes between the common prefix and suffix of two sequences, followed
+// by the suffix, like `lcs::trimmed`
+struct Trimmed {
+    snakes: Box<dyn Iterator<Item = Snake> + Send>,
+    // the end of the last snake
+    last: (usize, usize),
+    suffix: Option<Snake>,
+    ids_a: Vec<u32>,
+    ids_b: Vec<u32>,
+}
+
+impl Iterator for Trimmed {
+    type Item = Snake;
+
+    fn next(&mut self) -> Option<Snake> {
+        if let Some(snake) = self.snakes.next() {
+            self.last = (snake.a + snake.len, snake.b + snake.len);
+            return Some(snake);
+        }
+        let suffix = self.suffix.take()?;
+        let [moved, suffix] = slide(&self.ids_a, &self.ids_b, self.last, suffix);
+        self.snakes = Box::new(std::iter::once(suffix));
+        Some(moved)
+    }
+}
+
+impl Iterator for DiffIter<'_> {
+    type Item = Difference;
+
+    fn next(&mut self) -> Option<Difference> {
+        loop {
+            if let Some(diff) = self.found.pop_front() {
+                return Some(diff.to_owned());
+            }
+            if self.done {
+                return None;
+            }
+            self.find();
+        }
+    }
+}
+
+impl FusedIterator for DiffIter<'_> {}
+
+#[test]
+fn test_diff_iter() {
+    let text1 = "Roses are red, violets are blue,\n\
+                 I wrote this library,\n\
+                 just for you.\n\
+                 (It's true).";
+    let text2 = "Roses are red, violets are blue,\n\
+                 I wrote this documentation,\n\
+                 just for you.\n\
+                 (It's quite true).";
+
+    for split in ["", " ", "\n"] {
+        assert_eq!(
+            DiffIter::new(text1, text2, split).collect::<Vec<_>>(),
+            crate::Changeset::new(text1, text2, split).diffs
+        );
+    }
+    // the added lines are moved past the `}` they start with, like the
+    // common suffix was never split off
+    let (text1, text2) = ("x\nm\n}", "y\nm\n}\nk\n}");
+    let diffs = vec![
+        Difference::Rem("x".to_string()),
+        Difference::Add("y".to_string()),
+        Difference::Same("m\n}".to_string()),
+        Difference::Add("k\n}".to_string()),
+    ];
+    assert_eq!(crate::Changeset::new(text1, text2, "\n").diffs, diffs);
+    assert_eq!(DiffIter::new(text1, text2, "\n").collect::<Vec<_>>(), diffs);
+
+    assert_eq!(DiffIter::new("", "", "\n").next(), None);
+    assert_eq!(
+        DiffIter::new("", "a", "\n").collect::<Vec<_>>(),
+        vec![Difference::Add("a".to_string())]
+    );
+}
+
+#[test]
+fn test_diff_iter_large_input() {
+    let text1 = (0..5_000)
+        .map(|i| format!("line {}", i % 100))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text2 = text1
+        .replace("line 7\n", "line seven\n")
+        .replacen("line 42\n", "", 10);
+
+    let mut iter = DiffIter::new(&text1, &text2, "\n");
+    let first = (0..7)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(iter.next(), Some(Difference::Same(first)));
+    assert_eq!(iter.next(), Some(Difference::Rem("line 7".to_string())));
+
+    assert_eq!(
+        DiffIter::new(&text1, &text2, "\n").collect::<Vec<_>>(),
+        crate::Changeset::new(&text1, &text2, "\n").diffs
+    );
+}
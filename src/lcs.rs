@@ -1,9 +1,20 @@
-use std::cmp::max;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+use std::time::Instant;
+
+#[cfg(feature = "unicode")]
+use crate::Normalization;
+use crate::options::Deadline;
+#[cfg(feature = "unicode")]
+use crate::tokenizer::Graphemes;
+use crate::tokenizer::{Split, Tokens};
+use crate::{Algorithm, DiffOptions, histogram, myers, patience, tokenizer};
 
 // strsplit is like `s.split(split)`, except that if `split` is "", it
 // trims the leading and trailing empty elements, since the `lcs`
 // logic won't handle those properly.
-fn strsplit<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
+pub fn strsplit<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
     let mut si = s.split(split);
     if split.is_empty() {
         si.next();
@@ -15,80 +26,331 @@ fn strsplit<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
     v
 }
 
-// finds the longest common subsequences
-// outputs the edit distance and a string containing
-// all chars both inputs have in common
+// tokenize splits `s` into the tokens configured by `options`, which are
+// parts of `s` in order
+pub fn tokenize<'a>(s: &'a str, options: &DiffOptions) -> Vec<&'a str> {
+    if let Some(tokenizer) = &options.tokenizer {
+        return tokenizer::checked(tokenizer, s);
+    }
+    // a letter and its combining accents can only be normalized together,
+    // so the char-level split keeps them in one grapheme cluster
+    #[cfg(feature = "unicode")]
+    if options.split.is_empty() && options.normalization != Normalization::None {
+        return tokenizer::checked(&Tokens::Graphemes(Graphemes), s);
+    }
+    tokenizer::checked(&Tokens::Split(Split(options.split.clone())), s)
+}
+
+// split_tokens splits `s` by `split` like `tokenize`, for the functions that
+// take a split instead of options. An empty `s` has no tokens, instead of
+// the one empty token, like in the differences of `merge::locate`.
+pub fn split_tokens<'a>(s: &'a str, split: &str) -> Vec<&'a str> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+    tokenize(s, &DiffOptions::new().split(split))
+}
+
+// bytesplit is like `strsplit` for bytes, returning the range of every token
+// of `s` split by `split`. If `split` is empty, every byte is a token, and an
+// empty `s` has no tokens at all.
+pub fn bytesplit(s: &[u8], split: &[u8]) -> Vec<Range<usize>> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+    if split.is_empty() {
+        return (0..s.len()).map(|i| i..i + 1).collect();
+    }
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + split.len() <= s.len() {
+        if s[i..].starts_with(split) {
+            tokens.push(start..i);
+            i += split.len();
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    tokens.push(start..s.len());
+    tokens
+}
+
+/// A run of `len` tokens that appear, in order, at index `a` of the original
+/// and at index `b` of the edited sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Snake {
+    pub a: usize,
+    pub b: usize,
+    pub len: usize,
+}
+
+// counts the tokens both sequences start with
+pub fn common_prefix<T: PartialEq>(orig: &[T], edit: &[T]) -> usize {
+    orig.iter().zip(edit).take_while(|(o, e)| o == e).count()
+}
+
+// counts the tokens both sequences end with
+pub fn common_suffix<T: PartialEq>(orig: &[T], edit: &[T]) -> usize {
+    orig.iter()
+        .rev()
+        .zip(edit.iter().rev())
+        .take_while(|(o, e)| o == e)
+        .count()
+}
+
+// the limits of `DiffOptions` the algorithms have to respect, fixed for
+// a single diff
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub linear_space_threshold: usize,
+    pub deadline: Option<Instant>,
+    // parts of the inputs with more tokens than this are diffed on their own
+    // thread, see `diff_parts`
+    #[cfg(feature = "parallel")]
+    pub parallel_threshold: usize,
+}
+
+// The default `Limits::parallel_threshold`. Smaller parts are not worth
+// sending to another thread.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 4096;
+
+impl From<&DiffOptions> for Limits {
+    fn from(options: &DiffOptions) -> Limits {
+        Limits {
+            linear_space_threshold: options.linear_space_threshold,
+            deadline: options.deadline.map(|deadline| match deadline {
+                Deadline::At(instant) => instant,
+                Deadline::After(timeout) => Instant::now() + timeout,
+            }),
+            #[cfg(feature = "parallel")]
+            parallel_threshold: PARALLEL_THRESHOLD,
+        }
+    }
+}
+
+// checks whether the algorithms have run out of time, and should report what
+// they did not compare yet as changed
+pub fn expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+// appends `snake` to `snakes`, joining it with the last one when they touch
+pub fn push(snakes: &mut Vec<Snake>, snake: Snake) {
+    if snake.len == 0 {
+        return;
+    }
+    match snakes.last_mut() {
+        Some(last) if last.a + last.len == snake.a && last.b + last.len == snake.b => {
+            last.len += snake.len;
+        }
+        _ => snakes.push(snake),
+    }
+}
+
+// checks whether `diff_parts` diffs parts with `tokens` tokens in total on
+// separate threads
+#[cfg(feature = "parallel")]
+pub const fn parallel(tokens: usize, limits: Limits) -> bool {
+    tokens > limits.parallel_threshold
+}
+
+// without the `parallel` feature, parts are never diffed on separate threads
+#[cfg(not(feature = "parallel"))]
+pub const fn parallel(_tokens: usize, _limits: Limits) -> bool {
+    false
+}
+
+// appends the snakes `diff` finds in every one of `parts` to `snakes`, in
+// order, where the parts are independent of each other and have `tokens`
+// tokens in total
 //
-// This algorithm is based on
-// https://en.wikipedia.org/wiki/Longest_common_subsequence_problem#Code_for_the_dynamic_programming_solution
-#[expect(non_snake_case)]
-pub fn lcs(orig: &str, edit: &str, split: &str) -> (i128, String) {
-    // make list by custom splits
-    let a = strsplit(orig, split);
-    let b = strsplit(edit, split);
-
-    let N = a.len();
-    let M = b.len();
-
-    let mut idx: Vec<usize> = vec![0; N * M];
-
-    for i in 0..N {
-        for j in 0..M {
-            if b[j] == a[i] {
-                if i == 0 || j == 0 {
-                    idx[i * M + j] = 1;
-                } else {
-                    idx[i * M + j] = idx[(i - 1) * M + j - 1] + 1;
-                }
-            } else if i == 0 {
-                if j == 0 {
-                    idx[i * M + j] = 0;
-                } else {
-                    idx[i * M + j] = idx[i * M + j - 1];
-                }
-            } else if j == 0 {
-                idx[i * M + j] = idx[(i - 1) * M + j];
-            } else {
-                idx[i * M + j] = max(idx[i * M + j - 1], idx[(i - 1) * M + j]);
-            }
+// With the `parallel` feature, the parts are diffed on separate threads if
+// they are large enough. The snakes are the same either way.
+#[cfg_attr(not(feature = "parallel"), expect(unused_variables))] // Only needed in parallel
+pub fn diff_parts<P, F>(
+    parts: &[P],
+    tokens: usize,
+    limits: Limits,
+    snakes: &mut Vec<Snake>,
+    diff: F,
+) where
+    P: Sync,
+    F: Fn(&P, &mut Vec<Snake>) + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if parallel(tokens, limits) {
+        use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+        let found = parts
+            .par_iter()
+            .map(|part| {
+                let mut snakes = Vec::new();
+                diff(part, &mut snakes);
+                snakes
+            })
+            .collect::<Vec<_>>();
+        for snake in found.into_iter().flatten() {
+            push(snakes, snake);
         }
+        return;
+    }
+
+    for part in parts {
+        diff(part, snakes);
+    }
+}
+
+// finds the longest common subsequence of two token sequences
+// outputs the runs of tokens both inputs have in common, in order
+//
+// The edit distance between both sequences is the number of tokens that
+// are not part of any of the returned runs.
+//
+// Every algorithm matches the common prefix and suffix of the inputs up
+// front, and only compares the tokens between them.
+pub fn lcs<T: Eq + Hash>(orig: &[T], edit: &[T], options: &DiffOptions) -> Vec<Snake> {
+    let limits = Limits::from(options);
+    let (orig, edit) = intern(orig, edit);
+    trimmed(&orig, &edit, |orig, edit| match options.algorithm {
+        Algorithm::Myers => myers(orig, edit, limits),
+        Algorithm::Patience => patience::diff(orig, edit, limits),
+        Algorithm::Histogram => histogram::diff(orig, edit, limits),
+    })
+}
+
+// matches the common prefix and suffix of `orig` and `edit`, and finds the
+// snakes of the tokens between them with `diff`, see `slide`
+pub fn trimmed<T: PartialEq, F>(orig: &[T], edit: &[T], diff: F) -> Vec<Snake>
+where
+    F: FnOnce(&[T], &[T]) -> Vec<Snake>,
+{
+    let prefix = common_prefix(orig, edit);
+    let suffix = common_suffix(&orig[prefix..], &edit[prefix..]);
+    let (end_a, end_b) = (orig.len() - suffix, edit.len() - suffix);
+    let rest = diff(&orig[prefix..end_a], &edit[prefix..end_b]);
+
+    let mut snakes = Vec::with_capacity(rest.len() + 2);
+    push(
+        &mut snakes,
+        Snake {
+            a: 0,
+            b: 0,
+            len: prefix,
+        },
+    );
+    for snake in rest {
+        push(
+            &mut snakes,
+            Snake {
+                a: prefix + snake.a,
+                b: prefix + snake.b,
+                len: snake.len,
+            },
+        );
+    }
+
+    let last = snakes
+        .last()
+        .map_or((0, 0), |last| (last.a + last.len, last.b + last.len));
+    let suffix = Snake {
+        a: end_a,
+        b: end_b,
+        len: suffix,
+    };
+    for snake in slide(orig, edit, last, suffix) {
+        push(&mut snakes, snake);
     }
+    snakes
+}
+
+// replaces every token with an id, the same for equal tokens, so that the
+// algorithms compare and hash integers instead of the tokens themselves
+pub fn intern<T: Eq + Hash>(orig: &[T], edit: &[T]) -> (Vec<u32>, Vec<u32>) {
+    let mut ids: HashMap<&T, u32> = HashMap::with_capacity(orig.len());
+    let mut id = |token| {
+        let next = u32::try_from(ids.len()).expect("at most u32::MAX distinct tokens");
+        *ids.entry(token).or_insert(next)
+    };
+    let orig = orig.iter().map(&mut id).collect();
+    let edit = edit.iter().map(&mut id).collect();
+    (orig, edit)
+}
 
-    let mut i = (N as i128) - 1;
-    let mut j = (M as i128) - 1;
-    let mut lcs = Vec::new();
-
-    while i >= 0 && j >= 0 {
-        #[expect(clippy::cast_sign_loss)] // Already validated line `let mut i = (N as i128) - 1;`
-        let ui = i as usize;
-        #[expect(clippy::cast_sign_loss)] // Already validated line `let mut j = (M as i128) - 1;`
-        let uj = j as usize;
-        if a[ui] == b[uj] {
-            lcs.push(a[ui]);
-            i -= 1;
-            j -= 1;
-        } else if j == 0 && i == 0 {
-            break;
-        } else if i == 0 || idx[ui * M + uj - 1] > idx[(ui - 1) * M + uj] {
-            j -= 1;
+// moves a last addition or removal, from the end `last` of the snakes before
+// the common `suffix` up to it, past the tokens it starts like the suffix,
+// returning the snake of the tokens matched instead, and the rest of the suffix
+//
+// The algorithms would have put it there if the suffix had been part of
+// their inputs, like for a block of code that was added before a `}`.
+pub fn slide<T: PartialEq>(
+    orig: &[T],
+    edit: &[T],
+    last: (usize, usize),
+    suffix: Snake,
+) -> [Snake; 2] {
+    let (a, b) = last;
+    let moves = |k: usize| {
+        if a == suffix.a {
+            b < suffix.b && edit[b + k] == edit[suffix.b + k]
         } else {
-            i -= 1;
+            b == suffix.b && orig[a + k] == orig[suffix.a + k]
         }
-    }
+    };
+    let len = (0..suffix.len).take_while(|&k| moves(k)).count();
+    [
+        Snake { a, b, len },
+        Snake {
+            a: suffix.a + len,
+            b: suffix.b + len,
+            len: suffix.len - len,
+        },
+    ]
+}
 
-    lcs.reverse();
-    ((N + M - 2 * lcs.len()) as i128, lcs.join(split))
+// runs Myers' algorithm on the tokens between the common prefix and suffix
+// of the inputs, switching to its linear space variant for inputs above the
+// threshold of `limits`
+pub fn myers<T: PartialEq + Sync>(orig: &[T], edit: &[T], limits: Limits) -> Vec<Snake> {
+    trimmed(orig, edit, |orig, edit| {
+        if orig.len() + edit.len() > limits.linear_space_threshold {
+            myers::diff_linear(orig, edit, limits)
+        } else {
+            myers::diff(orig, edit, limits.deadline)
+        }
+    })
+}
+
+// joins the tokens of the longest common subsequence with `split`,
+// returning them alongside the edit distance
+#[cfg(test)]
+fn common(orig: &str, edit: &str, split: &str) -> (i128, String) {
+    let options = DiffOptions::new().split(split);
+    let a = tokenize(orig, &options);
+    let b = tokenize(edit, &options);
+    let common = lcs(&a, &b, &options)
+        .iter()
+        .flat_map(|snake| &a[snake.a..snake.a + snake.len])
+        .copied()
+        .collect::<Vec<_>>();
+    (
+        (a.len() + b.len() - 2 * common.len()) as i128,
+        common.join(split),
+    )
 }
 
 #[test]
 fn test_lcs() {
-    assert_eq!(lcs("test", "tost", ""), (2, "tst".to_string()));
-    assert_eq!(lcs("test", "test", ""), (0, "test".to_string()));
+    assert_eq!(common("test", "tost", ""), (2, "tst".to_string()));
+    assert_eq!(common("test", "test", ""), (0, "test".to_string()));
 
-    assert_eq!(lcs("test", "test", " "), (0, "test".to_string()));
+    assert_eq!(common("test", "test", " "), (0, "test".to_string()));
 
     assert_eq!(
-        lcs(
+        common(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             "",
@@ -96,7 +358,7 @@ fn test_lcs() {
         (16, "The quick brown o ps over the lazy ".to_string())
     );
     assert_eq!(
-        lcs(
+        common(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             " ",
@@ -105,7 +367,7 @@ fn test_lcs() {
     );
 
     assert_eq!(
-        lcs(
+        common(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown dog leaps over the lazy cat",
             "\n",
@@ -113,7 +375,7 @@ fn test_lcs() {
         (2, String::new())
     );
     assert_eq!(
-        lcs(
+        common(
             "The quick brown fox jumps over the lazy dog",
             "The quick brown fox jumps over the lazy dog",
             "\n",
@@ -122,11 +384,143 @@ fn test_lcs() {
     );
 
     assert_eq!(
-        lcs("a b : c", "b a : b : c", " "),
+        common("a b : c", "b a : b : c", " "),
         (2, "a b : c".to_string())
     );
 
-    assert_eq!(lcs("", "a b c", ""), (5, String::new()));
+    assert_eq!(common("", "a b c", ""), (5, String::new()));
+
+    assert_eq!(common("", " a", " "), (1, String::new()));
+}
+
+#[test]
+fn test_bytesplit() {
+    assert_eq!(bytesplit(b"a\nbc\n", b"\n"), vec![0..1, 2..4, 5..5]);
+    assert_eq!(bytesplit(b"a::b", b"::"), vec![0..1, 3..4]);
+    assert_eq!(bytesplit(b"ab", b""), vec![0..1, 1..2]);
+    assert_eq!(bytesplit(b"", b"\n"), Vec::<Range<usize>>::new());
+    assert_eq!(bytesplit(b"ab", b"abc"), vec![0..2]);
+}
+
+#[test]
+fn test_intern() {
+    assert_eq!(
+        intern(&["a", "b", "a"], &["c", "b", "a"]),
+        (vec![0, 1, 0], vec![2, 1, 0])
+    );
+    assert_eq!(intern::<&str>(&[], &[]), (vec![], vec![]));
+}
+
+#[test]
+fn test_myers_trimmed() {
+    let limits = Limits::from(&DiffOptions::new());
+    assert_eq!(
+        myers(&[1, 2, 3, 4], &[1, 2, 4], limits),
+        vec![Snake { a: 0, b: 0, len: 2 }, Snake { a: 3, b: 2, len: 1 }]
+    );
+    assert_eq!(
+        myers(&[1, 2], &[1, 2], limits),
+        vec![Snake { a: 0, b: 0, len: 2 }]
+    );
+}
+
+#[test]
+fn test_lcs_trimmed() {
+    // a change before a long suffix of repeated tokens, which every
+    // algorithm keeps together
+    let suffix = (0..1000).map(|i| i % 3).collect::<Vec<_>>();
+    let orig = [&[7, 0, 7][..], &suffix].concat();
+    let edit = [&[7, 8][..], &suffix].concat();
+    for algorithm in [Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram] {
+        let options = DiffOptions::new().algorithm(algorithm);
+        assert_eq!(
+            lcs(&orig, &edit, &options),
+            vec![
+                Snake { a: 0, b: 0, len: 1 },
+                Snake {
+                    a: 3,
+                    b: 2,
+                    len: 1000
+                }
+            ]
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn test_diff_parts_parallel() {
+    // diffing parts on other threads gives the very same snakes
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(orig: Vec<u8>, edit: Vec<u8>) -> bool {
+        let serial = Limits {
+            linear_space_threshold: 0,
+            parallel_threshold: usize::MAX,
+            ..Limits::from(&DiffOptions::new())
+        };
+        let parallel = Limits {
+            parallel_threshold: 0,
+            ..serial
+        };
+        let orig = orig.iter().map(|token| token % 8).collect::<Vec<_>>();
+        let edit = edit.iter().map(|token| token % 8).collect::<Vec<_>>();
+
+        myers(&orig, &edit, parallel) == myers(&orig, &edit, serial)
+            && patience::diff(&orig, &edit, parallel) == patience::diff(&orig, &edit, serial)
+            && histogram::diff(&orig, &edit, parallel) == histogram::diff(&orig, &edit, serial)
+    }
+
+    quickcheck::quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> bool);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_tokenize_graphemes() {
+    let options = DiffOptions::new().graphemes();
+    assert_eq!(
+        tokenize("a\u{1f44b}\u{1f3fd}e\u{301}\r\n", &options),
+        vec!["a", "\u{1f44b}\u{1f3fd}", "e\u{301}", "\r\n"]
+    );
+    assert!(tokenize("", &options).is_empty());
+    assert_eq!(tokenize("a b", &options.split(" ")), vec!["a", "b"]);
+
+    let options = DiffOptions::new().normalization(Normalization::Nfc);
+    assert_eq!(
+        tokenize("cafe\u{301}", &options),
+        vec!["c", "a", "f", "e\u{301}"]
+    );
+    assert_eq!(
+        crate::Changeset::with_options("caf\u{e9}", "cafe\u{301}", &options).distance,
+        0
+    );
+    assert_eq!(tokenize("e\u{301}", &options.split(" ")), vec!["e\u{301}"]);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_tokenize_words() {
+    let options = DiffOptions::new().words();
+    assert_eq!(
+        tokenize("Roses are  red,\tdon't\r\n(3.14)", &options),
+        vec![
+            "Roses", " ", "are", "  ", "red", ",", "\t", "don't", "\r\n", "(", "3.14", ")"
+        ]
+    );
+    assert!(tokenize("", &options).is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_tokenize_regex() {
+    let regex = regex::Regex::new(r"\w+|[^\w\s]").unwrap();
+    let options = DiffOptions::new().regex(regex);
+    assert_eq!(
+        tokenize("fn main() {}", &options),
+        vec!["fn", "main", "(", ")", "{", "}"]
+    );
+    assert!(tokenize("", &options).is_empty());
 
-    assert_eq!(lcs("", " a", " "), (1, String::new()));
+    let options = DiffOptions::new().regex_split(regex::Regex::new(r"\s+").unwrap());
+    assert_eq!(tokenize("a \t b\nc", &options), vec!["a", "b", "c"]);
+    assert_eq!(tokenize("", &options), vec![""]);
 }
@@ -7,7 +7,7 @@
 //!
 //! ```toml
 //! [dependencies]
-//! difference_rs = "3.0"
+//! difference_rs = "4.0"
 //! ```
 //!
 //! Now you can use the crate in your code
@@ -35,15 +35,42 @@
 #![deny(missing_docs)]
 #![deny(warnings)]
 
+mod bit_parallel;
+mod bytes;
+mod compare;
 mod display;
+mod histogram;
+mod iter;
 mod lcs;
 mod merge;
 mod multi;
-
+mod myers;
+mod options;
+mod patience;
+mod position;
+mod slice;
+mod tokenizer;
+
+use std::borrow::Cow;
 use std::char::REPLACEMENT_CHARACTER;
 
-use crate::lcs::lcs;
-use crate::merge::merge;
+// the examples of the README, which are tested like the ones of the docs
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+struct ReadmeDoctests;
+
+pub use crate::bytes::diff_bytes;
+#[cfg(feature = "unicode")]
+pub use crate::compare::Normalization;
+use crate::compare::common;
+pub use crate::compare::{LineEndings, Whitespace};
+pub use crate::iter::DiffIter;
+use crate::lcs::{lcs, split_tokens, tokenize};
+use crate::merge::{Located, locate, merge_short_sames};
+pub use crate::options::DiffOptions;
+pub use crate::position::{Position, PositionedDifference, Span};
+pub use crate::slice::{SliceDifference, diff_slices, diff_slices_with_options};
+pub use crate::tokenizer::{Chars, Lines, Spaces, Split, Tokenizer};
 
 /// Defines the contents of a changeset
 /// Changesets will be delivered in order of appearance in the original string
@@ -59,6 +86,48 @@ pub enum Difference {
     Rem(String),
 }
 
+/// Defines the contents of a borrowed changeset, like `Difference` but
+/// referring to the text of the original and edited strings
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DifferenceRef<'a> {
+    /// Sequences that are the same
+    Same(&'a str),
+    /// Sequences that are an addition (don't appear in the first string)
+    Add(&'a str),
+    /// Sequences that are a removal (don't appear in the second string)
+    Rem(&'a str),
+}
+
+impl DifferenceRef<'_> {
+    /// Copies the text of the difference into an owned `Difference`.
+    #[must_use]
+    pub fn to_owned(self) -> Difference {
+        match self {
+            DifferenceRef::Same(x) => Difference::Same(x.to_string()),
+            DifferenceRef::Add(x) => Difference::Add(x.to_string()),
+            DifferenceRef::Rem(x) => Difference::Rem(x.to_string()),
+        }
+    }
+}
+
+/// The algorithms available to find the differences between two texts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// Myers' O(ND) algorithm, which finds a minimal changeset
+    #[default]
+    Myers,
+    /// Patience diff, which anchors on tokens that occur exactly once in both
+    /// texts. The changeset may be larger than a minimal one, but repeated
+    /// tokens like braces and blank lines are less likely to be aligned
+    /// with unrelated ones.
+    Patience,
+    /// Histogram diff, as in `git diff --histogram`. Like patience diff, but
+    /// anchoring on the tokens that occur the least in the original text, so
+    /// it still finds anchors when no token is unique.
+    Histogram,
+}
+
 /// The information about a full changeset
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -73,6 +142,24 @@ pub struct Changeset {
     pub distance: i128,
 }
 
+/// The information about a full changeset, borrowing the text of its
+/// differences from the original and edited strings instead of copying it
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangesetRef<'a> {
+    /// An ordered vector of `DifferenceRef` objects, corresponding
+    /// to the differences within the text
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub diffs: Vec<DifferenceRef<'a>>,
+    /// The split used when creating the `ChangesetRef`, borrowed if it was
+    /// given as a `&str`
+    /// Common splits are `""` for char-level, `" "` for word-level and `"\n"` for line-level.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub split: Cow<'a, str>,
+    /// The edit distance of the `ChangesetRef`
+    pub distance: i128,
+}
+
 /// The information about a full changeset when regarding a multi split changeset
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -99,6 +186,11 @@ impl Changeset {
     /// Outputs the edit distance (how much the two strings differ) and a "changeset", that is
     /// a `Vec` containing `Difference`s.
     ///
+    /// The changeset is a minimal one, found with Myers' algorithm. When
+    /// several changesets are equally small, the one returned may group the
+    /// differences unlike 3.2.0 and earlier versions, which filled a table of
+    /// every pair of tokens and could also return a larger changeset.
+    ///
     /// # Examples
     ///
     /// ```
@@ -115,12 +207,113 @@ impl Changeset {
     /// ```
     #[must_use]
     pub fn new(orig: &str, edit: &str, split: &str) -> Changeset {
-        let (dist, common) = lcs(orig, edit, split);
-        Changeset {
-            diffs: merge(orig, edit, &common, split),
-            split: split.to_string(),
-            distance: dist,
+        Changeset::with_options(orig, edit, &DiffOptions::new().split(split))
+    }
+
+    /// Calculates the edit distance and the changeset for two given strings,
+    /// like `Changeset::new`, configured by the given `DiffOptions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Algorithm, Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split("\n").algorithm(Algorithm::Patience);
+    /// let changeset = Changeset::with_options(
+    ///     "if a\n{\n    b();\n}\n\nif c\n{\n    d();\n}",
+    ///     "if c\n{\n    d();\n}\n\nif a\n{\n    b();\n}",
+    ///     &options,
+    /// );
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Rem("if a\n{\n    b();\n}\n".to_string()),
+    ///     Difference::Same("if c\n{\n    d();\n}".to_string()),
+    ///     Difference::Add("\nif a\n{\n    b();\n}".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn with_options(orig: &str, edit: &str, options: &DiffOptions) -> Changeset {
+        ChangesetRef::with_options(orig, edit, options).to_owned()
+    }
+
+    /// Calculates the differences between two given strings like
+    /// `Changeset::new`, yielding them one at a time.
+    ///
+    /// For large inputs, the differences are yielded as soon as they are
+    /// found, so callers that stop early don't compare the whole strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, Difference};
+    ///
+    /// let mut diffs = Changeset::iter_diff("test", "tent", "");
+    ///
+    /// assert_eq!(diffs.next(), Some(Difference::Same("te".to_string())));
+    /// assert_eq!(diffs.next(), Some(Difference::Rem("s".to_string())));
+    /// ```
+    #[must_use]
+    pub fn iter_diff<'a>(orig: &'a str, edit: &'a str, split: &'a str) -> DiffIter<'a> {
+        DiffIter::new(orig, edit, split)
+    }
+
+    /// Calculates the edit distance between two given strings like
+    /// `Changeset::new`, but only if it is at most `max_distance`.
+    ///
+    /// Gives up as soon as the distance is known to be larger, which is much
+    /// faster than calculating the whole changeset of very different strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::Changeset;
+    ///
+    /// assert_eq!(Changeset::distance_within("test", "tent", "", 2), Some(2));
+    /// assert_eq!(Changeset::distance_within("test", "tent", "", 1), None);
+    /// ```
+    #[must_use]
+    pub fn distance_within(
+        orig: &str,
+        edit: &str,
+        split: &str,
+        max_distance: i128,
+    ) -> Option<i128> {
+        if max_distance < 0 {
+            return None;
         }
+        let max_distance = usize::try_from(max_distance).unwrap_or(usize::MAX);
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        myers::distance_within(&a, &b, max_distance).map(|distance| distance as i128)
+    }
+
+    /// Calculates only the edit distance between two given strings, which is
+    /// the `distance` of `Changeset::new`, without building the changeset.
+    ///
+    /// For char-level splits, the common subsequence of strings up to a few
+    /// thousand chars is counted 64 chars at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::Changeset;
+    ///
+    /// assert_eq!(Changeset::edit_distance("test", "tent", ""), 2);
+    /// assert_eq!(Changeset::edit_distance("Rust", "Dust", ""), 2);
+    /// ```
+    #[must_use]
+    pub fn edit_distance(orig: &str, edit: &str, split: &str) -> i128 {
+        let a = split_tokens(orig, split);
+        let b = split_tokens(edit, split);
+        let same = if split.is_empty() && a.len().min(b.len()) <= bit_parallel::MAX_LEN {
+            bit_parallel::lcs_len(&a, &b)
+        } else {
+            lcs(&a, &b, &DiffOptions::new())
+                .iter()
+                .map(|snake| snake.len)
+                .sum()
+        };
+        (a.len() + b.len() - 2 * same) as i128
     }
 
     /// Creates a `Changeset` with multiple possible splits.
@@ -180,6 +373,102 @@ impl Changeset {
     }
 }
 
+impl<'a> ChangesetRef<'a> {
+    /// Calculates the edit distance and the changeset for two given strings
+    /// like `Changeset::new`, without copying their text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{ChangesetRef, DifferenceRef};
+    ///
+    /// let changeset = ChangesetRef::new("test", "tent", "");
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     DifferenceRef::Same("te"),
+    ///     DifferenceRef::Rem("s"),
+    ///     DifferenceRef::Add("n"),
+    ///     DifferenceRef::Same("t")
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn new(orig: &'a str, edit: &'a str, split: &'a str) -> ChangesetRef<'a> {
+        ChangesetRef {
+            split: Cow::Borrowed(split),
+            ..ChangesetRef::with_options(orig, edit, &DiffOptions::new().split(split))
+        }
+    }
+
+    /// Calculates the edit distance and the changeset for two given strings
+    /// like `Changeset::with_options`, without copying their text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{ChangesetRef, DiffOptions, DifferenceRef};
+    ///
+    /// let changeset = ChangesetRef::with_options("a b", "a c", &DiffOptions::new().split(" "));
+    ///
+    /// assert_eq!(changeset.split, " ");
+    /// assert_eq!(changeset.diffs, vec![
+    ///     DifferenceRef::Same("a"),
+    ///     DifferenceRef::Rem("b"),
+    ///     DifferenceRef::Add("c"),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn with_options(orig: &'a str, edit: &'a str, options: &DiffOptions) -> ChangesetRef<'a> {
+        let (located, distance) = ChangesetRef::located(orig, edit, options);
+        ChangesetRef {
+            diffs: located.into_iter().map(|located| located.diff).collect(),
+            split: Cow::Owned(options.split.clone()),
+            distance,
+        }
+    }
+
+    // finds the differences between `orig` and `edit` like `with_options`,
+    // along with where they are, and the edit distance
+    fn located(orig: &'a str, edit: &'a str, options: &DiffOptions) -> (Vec<Located<'a>>, i128) {
+        let a = tokenize(orig, options);
+        let b = tokenize(edit, options);
+        let mut common = common(&a, &b, options);
+        if options.merge_short_sames > 0 {
+            common = merge_short_sames(&common, (a.len(), b.len()), options.merge_short_sames);
+        }
+        let (located, changed) = locate(
+            orig,
+            edit,
+            &a,
+            &b,
+            &common,
+            &options.split,
+            options.ignore_blank_lines,
+        );
+        (located, changed as i128)
+    }
+
+    /// Copies the text of the changeset into an owned `Changeset`.
+    #[must_use]
+    pub fn to_owned(&self) -> Changeset {
+        Changeset {
+            diffs: self
+                .diffs
+                .iter()
+                .copied()
+                .map(DifferenceRef::to_owned)
+                .collect(),
+            split: self.split.to_string(),
+            distance: self.distance,
+        }
+    }
+}
+
+impl From<ChangesetRef<'_>> for Changeset {
+    fn from(changeset: ChangesetRef<'_>) -> Changeset {
+        changeset.to_owned()
+    }
+}
+
 /// Assert the difference between two strings. Works like diff, but takes
 /// a fourth parameter that is the expected edit distance (e.g. 0 if you want to
 /// test for equality).
@@ -417,3 +706,262 @@ fn test_multi_uri_pattern() {
 
     assert_eq!(cg, expected);
 }
+
+#[test]
+fn test_diff_large_input() {
+    let text1 = (0..50_000)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text2 = text1.replace("line 25000\n", "line twenty-five thousand\n");
+
+    let changeset = Changeset::new(&text1, &text2, "\n");
+
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(changeset.diffs.len(), 4);
+    assert_eq!(
+        changeset.diffs[1],
+        Difference::Rem("line 25000".to_string())
+    );
+    assert_eq!(
+        changeset.diffs[2],
+        Difference::Add("line twenty-five thousand".to_string())
+    );
+}
+
+#[test]
+fn test_diff_large_char_input() {
+    let text1 = "{\"key\":[1,2,3],\"value\":\"abc\"}".repeat(10_000);
+    let text2 = format!("{}x{}", &text1[..150_000], &text1[150_001..]);
+
+    let changeset = Changeset::new(&text1, &text2, "");
+
+    assert_eq!(changeset.distance, 2);
+    assert_eq!(changeset.diffs.len(), 4);
+    assert_eq!(changeset.diffs[2], Difference::Add("x".to_string()));
+}
+
+#[test]
+fn test_diff_patience() {
+    let text1 = "if a\n{\n    b();\n}\n\nif c\n{\n    d();\n}";
+    let text2 = "if c\n{\n    d();\n}\n\nif a\n{\n    b();\n}";
+
+    // Myers' minimal changeset aligns the unrelated braces
+    let changeset = Changeset::with_options(
+        text1,
+        text2,
+        &DiffOptions::new().split("\n").algorithm(Algorithm::Myers),
+    );
+    assert_eq!(changeset.distance, 8);
+    assert_eq!(changeset.diffs[1], Difference::Add("if c".to_string()));
+    assert_eq!(changeset.diffs[2], Difference::Same("{".to_string()));
+
+    let changeset = Changeset::with_options(
+        text1,
+        text2,
+        &DiffOptions::new()
+            .split("\n")
+            .algorithm(Algorithm::Patience),
+    );
+    assert_eq!(changeset.distance, 10);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("if a\n{\n    b();\n}\n".to_string()),
+            Difference::Same("if c\n{\n    d();\n}".to_string()),
+            Difference::Add("\nif a\n{\n    b();\n}".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_histogram() {
+    // the lines of the `c` block occur the least, so they are kept together
+    // although matching the repeated `a` block gives a smaller changeset
+    let text1 = "if a\n{\n    b();\n}\nif a\n{\n    b();\n}\n\nif c\n{\n    d();\n}";
+    let text2 = "if c\n{\n    d();\n}\n\nif a\n{\n    b();\n}\nif a\n{\n    b();\n}";
+
+    let changeset = Changeset::with_options(
+        text1,
+        text2,
+        &DiffOptions::new().split("\n").algorithm(Algorithm::Myers),
+    );
+    assert_eq!(changeset.distance, 10);
+
+    let changeset = Changeset::with_options(
+        text1,
+        text2,
+        &DiffOptions::new()
+            .split("\n")
+            .algorithm(Algorithm::Histogram),
+    );
+    assert_eq!(changeset.distance, 18);
+    assert_eq!(
+        changeset.diffs,
+        vec![
+            Difference::Rem("if a\n{\n    b();\n}\nif a\n{\n    b();\n}\n".to_string()),
+            Difference::Same("if c\n{\n    d();\n}".to_string()),
+            Difference::Add("\nif a\n{\n    b();\n}\nif a\n{\n    b();\n}".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_options() {
+    let text1 = (0..1000)
+        .map(|i| (i % 7).to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text2 = text1.replacen("3\n", "x\n", 5);
+
+    // both variants of Myers' algorithm find a minimal changeset
+    let options = DiffOptions::new().split("\n");
+    let changeset = Changeset::with_options(&text1, &text2, &options);
+    assert_eq!(changeset, Changeset::new(&text1, &text2, "\n"));
+    assert_eq!(changeset.distance, 10);
+
+    let options = options.linear_space_threshold(0);
+    let changeset = Changeset::with_options(&text1, &text2, &options);
+    assert_eq!(changeset.distance, 10);
+}
+
+#[test]
+fn test_diff_deadline() {
+    let text1 = "a b c d e";
+    let text2 = "a x c y e";
+    let rebuild = |diffs: &[Difference], keep: fn(&Difference) -> Option<&String>| {
+        diffs
+            .iter()
+            .filter_map(keep)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    for algorithm in [Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram] {
+        let options = DiffOptions::new()
+            .split(" ")
+            .algorithm(algorithm)
+            .timeout(std::time::Duration::ZERO);
+        let changeset = Changeset::with_options(text1, text2, &options);
+
+        // out of time, the changeset isn't minimal but still valid
+        assert!(changeset.distance > 4);
+        let orig = rebuild(&changeset.diffs, |diff| match diff {
+            Difference::Same(s) | Difference::Rem(s) => Some(s),
+            Difference::Add(_) => None,
+        });
+        let edit = rebuild(&changeset.diffs, |diff| match diff {
+            Difference::Same(s) | Difference::Add(s) => Some(s),
+            Difference::Rem(_) => None,
+        });
+        assert_eq!((orig.as_str(), edit.as_str()), (text1, text2));
+    }
+}
+
+#[test]
+fn test_distance_within() {
+    let text1 = "Roses are red, violets are blue,\n\
+                 I wrote this library,\n\
+                 just for you.\n\
+                 (It's true).";
+    let text2 = "Roses are red, violets are blue,\n\
+                 I wrote this documentation,\n\
+                 just for you.\n\
+                 (It's quite true).";
+
+    for split in ["", " ", "\n"] {
+        let distance = Changeset::new(text1, text2, split).distance;
+        assert_eq!(
+            Changeset::distance_within(text1, text2, split, distance),
+            Some(distance)
+        );
+        assert_eq!(
+            Changeset::distance_within(text1, text2, split, distance - 1),
+            None
+        );
+    }
+    assert_eq!(Changeset::distance_within("a", "a", "", -1), None);
+    assert_eq!(
+        Changeset::distance_within("ab", "ba", "", i128::MAX),
+        Some(2)
+    );
+    assert_eq!(Changeset::distance_within("", "", "\n", 0), Some(0));
+}
+
+#[test]
+fn test_edit_distance() {
+    let text1 = "The quick brown fox jumps over the lazy dog";
+    let text2 = "The quick brown dog leaps over the lazy cat";
+    for split in ["", " ", "\n"] {
+        assert_eq!(
+            Changeset::edit_distance(text1, text2, split),
+            Changeset::new(text1, text2, split).distance
+        );
+    }
+    assert_eq!(Changeset::edit_distance("", "abc", ""), 3);
+    // an empty string has no tokens, so nothing is removed from it
+    assert_eq!(Changeset::edit_distance("", "a", " "), 1);
+    assert_eq!(Changeset::new("", "a", " ").distance, 1);
+    assert_eq!(Changeset::distance_within("", "a", " ", 1), Some(1));
+
+    // longer than `bit_parallel::MAX_LEN`
+    let text1 = "abcd".repeat(1100);
+    let text2 = text1.replace("abcdabcdabcd", "abcdabxdabcd");
+    assert_eq!(
+        Changeset::edit_distance(&text1, &text2, ""),
+        Changeset::new(&text1, &text2, "").distance
+    );
+}
+
+#[test]
+fn test_distance_whitespace() {
+    let options = DiffOptions::new()
+        .split(" ")
+        .whitespace(Whitespace::IgnoreAll);
+    for (orig, edit, diffs) in [
+        ("", "\t", vec![Difference::Add("\t".to_string())]),
+        ("\t\t", "", vec![Difference::Rem("\t\t".to_string())]),
+        (
+            "a \t",
+            "a",
+            vec![
+                Difference::Same("a".to_string()),
+                Difference::Rem("\t".to_string()),
+            ],
+        ),
+        ("a\t", "a", vec![Difference::Same("a\t".to_string())]),
+    ] {
+        let changeset = Changeset::with_options(orig, edit, &options);
+        assert_eq!(changeset.diffs, diffs);
+        assert_eq!(
+            changeset.distance == 0,
+            diffs.iter().all(|d| matches!(d, Difference::Same(_)))
+        );
+    }
+}
+
+#[test]
+fn test_changeset_ref() {
+    let text1 = "Roses are red, violets are blue,\n\
+                 I wrote this library,\n\
+                 just for you.\n\
+                 (It's true).";
+    let text2 = "Roses are red, violets are blue,\n\
+                 I wrote this documentation,\n\
+                 just for you.\n\
+                 (It's quite true).";
+
+    for split in ["", " ", "\n"] {
+        let changeset = ChangesetRef::new(text1, text2, split);
+        assert_eq!(changeset.to_owned(), Changeset::new(text1, text2, split));
+        assert_eq!(
+            Changeset::from(changeset.clone()).to_string(),
+            changeset.to_string()
+        );
+    }
+
+    let changeset = ChangesetRef::new("", "", "\n");
+    assert_eq!(changeset.diffs, vec![]);
+    assert_eq!(changeset.distance, 0);
+}
@@ -1,91 +1,343 @@
-use crate::Difference;
+use std::ops::Range;
 
-// merges the changes from two strings, given a common substring
-pub fn merge(orig: &str, edit: &str, common: &str, split: &str) -> Vec<Difference> {
-    let mut ret = Vec::new();
+use crate::DifferenceRef;
+use crate::compare::is_blank;
+use crate::lcs::Snake;
 
-    let mut l = orig.split(split).peekable();
-    let mut r = edit.split(split).peekable();
-    let mut c = common.split(split).peekable();
+// a difference, along with the byte ranges of its text in the original and
+// the edited string
+//
+// The range in the string a difference is not a part of is empty, at the
+// start of the tokens after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Located<'a> {
+    pub diff: DifferenceRef<'a>,
+    pub orig: Range<usize>,
+    pub edit: Range<usize>,
+}
+
+// merges the changes from two token sequences, given the runs of tokens
+// they have in common, along with where every difference is and the number
+// of tokens added or removed
+//
+// The tokens are parts of `orig` and `edit`, split by `split`, and every
+// difference borrows the text of its tokens from them.
+//
+// If `ignore_blank` is set, the changes between two runs of common tokens
+// that only add or remove blank tokens are the same instead, see
+// `DiffOptions::ignore_blank_lines`.
+pub fn locate<'a>(
+    orig: &'a str,
+    edit: &'a str,
+    a: &[&'a str],
+    b: &[&'a str],
+    common: &[Snake],
+    split: &str,
+    ignore_blank: bool,
+) -> (Vec<Located<'a>>, usize) {
+    let ignored = |removed: &[&str], added: &[&str]| {
+        ignore_blank && removed.iter().chain(added).all(|token| is_blank(token))
+    };
 
     // Turn empty strings into [], not [""]
-    if orig.is_empty() {
-        l.next();
-    }
-    if edit.is_empty() {
-        r.next();
-    }
-    if common.is_empty() {
-        c.next();
-    }
+    let a = if a == [""] { &[][..] } else { a };
+    let b = if b == [""] { &[][..] } else { b };
+    let common = if a.is_empty() || b.is_empty() {
+        &[][..]
+    } else {
+        common
+    };
 
-    while l.peek().is_some() || r.peek().is_some() {
-        let mut same = Vec::new();
-        while l.peek().is_some() && l.peek() == c.peek() && r.peek() == c.peek() {
-            same.push(l.next().unwrap());
-            r.next();
-            c.next();
+    let mut located = Vec::new();
+    let mut changed = 0;
+    for (i, j, snake) in hunks(a, b, common) {
+        let removed = bytes(orig, a, i.clone());
+        let added = bytes(edit, b, j.clone());
+        if ignored(&a[i.clone()], &b[j.clone()]) {
+            same(&mut located, orig, edit, removed, added, i.is_empty());
+        } else {
+            changed += i.len() + j.len();
+            if !i.is_empty() {
+                located.push(Located {
+                    diff: DifferenceRef::Rem(&orig[removed.clone()]),
+                    orig: removed.clone(),
+                    edit: added.start..added.start,
+                });
+            }
+            if !j.is_empty() {
+                located.push(Located {
+                    diff: DifferenceRef::Add(&edit[added.clone()]),
+                    orig: bytes(orig, a, i.end..i.end),
+                    edit: added,
+                });
+            }
         }
-        if !same.is_empty() {
-            let joined = same.join(split);
-            if !split.is_empty() || !joined.is_empty() {
-                ret.push(Difference::Same(joined));
+        if snake.len > 0 {
+            let tokens = bytes(orig, a, snake.a..snake.a + snake.len);
+            if !split.is_empty() || !tokens.is_empty() {
+                let edit_tokens = bytes(edit, b, snake.b..snake.b + snake.len);
+                same(&mut located, orig, edit, tokens, edit_tokens, false);
             }
         }
+    }
+    (located, changed)
+}
 
-        let mut rem = Vec::new();
-        while l.peek().is_some() && l.peek() != c.peek() {
-            rem.push(l.next().unwrap());
-        }
-        if !rem.is_empty() {
-            ret.push(Difference::Rem(rem.join(split)));
-        }
+// the ranges of the tokens removed and added before each of the runs of
+// `common` tokens, and before the end
+fn hunks(
+    a: &[&str],
+    b: &[&str],
+    common: &[Snake],
+) -> impl Iterator<Item = (Range<usize>, Range<usize>, Snake)> {
+    let end = Snake {
+        a: a.len(),
+        b: b.len(),
+        len: 0,
+    };
+    let mut next = (0, 0);
+    common
+        .iter()
+        .copied()
+        .chain([end])
+        .filter_map(move |snake| {
+            let (i, j) = next;
+            next = (snake.a + snake.len, snake.b + snake.len);
+            (i < snake.a || j < snake.b || snake.len > 0).then_some((i..snake.a, j..snake.b, snake))
+        })
+}
 
-        let mut add = Vec::new();
-        while r.peek().is_some() && r.peek() != c.peek() {
-            add.push(r.next().unwrap());
+// adds text that is the same in both strings, at the byte ranges `orig` and
+// `edit`, to the differences, joining it to the one before if that is the
+// same too
+//
+// The text is taken from the original string, unless `edit_only` is set for
+// ignored changes that have no original tokens, and everything it is joined
+// to is only in the edited string too.
+fn same<'a>(
+    located: &mut Vec<Located<'a>>,
+    orig: &'a str,
+    edit: &'a str,
+    mut orig_range: Range<usize>,
+    mut edit_range: Range<usize>,
+    mut edit_only: bool,
+) {
+    if let Some(last) = located.pop_if(|last| matches!(last.diff, DifferenceRef::Same(_))) {
+        // only text taken from the edited string is there without any
+        // original text
+        let text = matches!(last.diff, DifferenceRef::Same(text) if !text.is_empty());
+        edit_only &= last.orig.is_empty() && (text || last.edit.is_empty());
+        orig_range.start = last.orig.start;
+        edit_range.start = last.edit.start;
+    }
+    let text = if edit_only {
+        &edit[edit_range.clone()]
+    } else {
+        &orig[orig_range.clone()]
+    };
+    located.push(Located {
+        diff: DifferenceRef::Same(text),
+        orig: orig_range,
+        edit: edit_range,
+    });
+}
+
+// drops the runs of at most `max_len` common tokens that have changes both
+// before and after them, so they are merged into those changes, see
+// `DiffOptions::merge_short_sames`
+//
+// `end` is where the sequences end, after the last of the `common` runs.
+pub fn merge_short_sames(common: &[Snake], end: (usize, usize), max_len: usize) -> Vec<Snake> {
+    let mut kept: Vec<Snake> = Vec::with_capacity(common.len());
+    for (k, snake) in common.iter().enumerate() {
+        let before = kept
+            .last()
+            .map_or((0, 0), |last| (last.a + last.len, last.b + last.len));
+        let after = common.get(k + 1).map_or(end, |next| (next.a, next.b));
+        let between =
+            before != (snake.a, snake.b) && after != (snake.a + snake.len, snake.b + snake.len);
+        if snake.len > max_len || !between {
+            kept.push(*snake);
         }
-        if !add.is_empty() {
-            ret.push(Difference::Add(add.join(split)));
+    }
+    kept
+}
+
+// the byte range of the consecutive `tokens[range]` of `s`, or where they
+// would start if there are none
+fn bytes(s: &str, tokens: &[&str], range: Range<usize>) -> Range<usize> {
+    let offset = |token: &str| token.as_ptr().addr() - s.as_ptr().addr();
+    if range.is_empty() {
+        let start = tokens
+            .get(range.start)
+            .map_or(s.len(), |&token| offset(token));
+        return start..start;
+    }
+    let (first, last) = (tokens[range.start], tokens[range.end - 1]);
+    offset(first)..offset(last) + last.len()
+}
+
+// the text of `s` from the first to the last of the consecutive `tokens`,
+// which is like joining them with the split between them
+pub fn span<'a>(s: &'a str, tokens: &[&str]) -> &'a str {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => {
+            let start = first.as_ptr().addr() - s.as_ptr().addr();
+            let end = last.as_ptr().addr() + last.len() - s.as_ptr().addr();
+            &s[start..end]
         }
+        _ => "",
     }
+}
 
-    ret
+// merges the changes from two strings split by `split` like `locate`
+#[cfg(test)]
+fn merge_str<'a>(
+    orig: &'a str,
+    edit: &'a str,
+    common: &[Snake],
+    split: &str,
+) -> Vec<DifferenceRef<'a>> {
+    let a = crate::lcs::split_tokens(orig, split);
+    let b = crate::lcs::split_tokens(edit, split);
+    locate(orig, edit, &a, &b, common, split, false)
+        .0
+        .into_iter()
+        .map(|located| located.diff)
+        .collect()
 }
 
 #[test]
 fn test_merge() {
     assert_eq!(
-        merge("testa", "tost", "tst", ""),
+        merge_str(
+            "testa",
+            "tost",
+            &[Snake { a: 0, b: 0, len: 1 }, Snake { a: 2, b: 2, len: 2 }],
+            "",
+        ),
         vec![
-            Difference::Same("t".to_string()),
-            Difference::Rem("e".to_string()),
-            Difference::Add("o".to_string()),
-            Difference::Same("st".to_string()),
-            Difference::Rem("a".to_string()),
+            DifferenceRef::Same("t"),
+            DifferenceRef::Rem("e"),
+            DifferenceRef::Add("o"),
+            DifferenceRef::Same("st"),
+            DifferenceRef::Rem("a"),
         ]
     );
 
+    assert_eq!(merge_str("", "a", &[], ""), vec![DifferenceRef::Add("a")]);
+
     assert_eq!(
-        merge("", "a", "", ""),
-        vec![Difference::Add("a".to_string())]
+        merge_str(
+            "a\nb",
+            "a\n\nb",
+            &[Snake { a: 0, b: 0, len: 1 }, Snake { a: 1, b: 2, len: 1 }],
+            "\n",
+        ),
+        vec![
+            DifferenceRef::Same("a"),
+            DifferenceRef::Add(""),
+            DifferenceRef::Same("b"),
+        ]
     );
 
     assert_eq!(
-        merge("a\nb", "a\n\nb", "a\nb", "\n"),
+        merge_str("a\n", "c\n", &[Snake { a: 1, b: 1, len: 1 }], "\n"),
         vec![
-            Difference::Same("a".to_string()),
-            Difference::Add(String::new()),
-            Difference::Same("b".to_string()),
+            DifferenceRef::Rem("a"),
+            DifferenceRef::Add("c"),
+            DifferenceRef::Same(""),
         ]
     );
 
     assert_eq!(
-        merge("a\n", "c\n", "\n", "\n"),
+        merge_str(
+            "a b c d",
+            "a x y d",
+            &[Snake { a: 0, b: 0, len: 1 }, Snake { a: 3, b: 3, len: 1 }],
+            " ",
+        ),
         vec![
-            Difference::Rem("a".to_string()),
-            Difference::Add("c".to_string()),
-            Difference::Same(String::new()),
+            DifferenceRef::Same("a"),
+            DifferenceRef::Rem("b c"),
+            DifferenceRef::Add("x y"),
+            DifferenceRef::Same("d"),
         ]
     );
 }
+
+#[test]
+fn test_locate_ignore_blank() {
+    let locate = |orig, edit| {
+        let a = crate::lcs::split_tokens(orig, "\n");
+        let b = crate::lcs::split_tokens(edit, "\n");
+        let common = crate::lcs::lcs(&a, &b, &crate::DiffOptions::new());
+        locate(orig, edit, &a, &b, &common, "\n", true)
+    };
+
+    assert_eq!(
+        locate("a\nb\nc", "a\n\nb\nc\n\n"),
+        (
+            vec![Located {
+                diff: DifferenceRef::Same("a\nb\nc"),
+                orig: 0..5,
+                edit: 0..8,
+            }],
+            0
+        )
+    );
+    assert_eq!(
+        locate("a\n\n\nb", "a\n \nc"),
+        (
+            vec![
+                Located {
+                    diff: DifferenceRef::Same("a"),
+                    orig: 0..1,
+                    edit: 0..1,
+                },
+                Located {
+                    diff: DifferenceRef::Rem("\n\nb"),
+                    orig: 2..5,
+                    edit: 2..2,
+                },
+                Located {
+                    diff: DifferenceRef::Add(" \nc"),
+                    orig: 5..5,
+                    edit: 2..5,
+                },
+            ],
+            5
+        )
+    );
+    assert_eq!(
+        locate("", "\n"),
+        (
+            vec![Located {
+                diff: DifferenceRef::Same("\n"),
+                orig: 0..0,
+                edit: 0..1,
+            }],
+            0
+        )
+    );
+}
+
+#[test]
+fn test_merge_short_sames() {
+    let snake = |a, b, len| Snake { a, b, len };
+    let common = [
+        snake(0, 0, 1),
+        snake(2, 2, 1),
+        snake(4, 3, 2),
+        snake(7, 5, 1),
+    ];
+
+    assert_eq!(merge_short_sames(&common, (8, 7), 0), common);
+    // the first run starts both sequences, and the last ends them
+    assert_eq!(
+        merge_short_sames(&common, (8, 6), 1),
+        [snake(0, 0, 1), snake(4, 3, 2), snake(7, 5, 1)]
+    );
+    assert_eq!(merge_short_sames(&common, (8, 7), 2), [snake(0, 0, 1)]);
+    assert!(merge_short_sames(&[], (1, 1), 1).is_empty());
+}
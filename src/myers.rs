@@ -0,0 +1,420 @@
+use std::ops::Range;
+use std::time::Instant;
+
+use crate::lcs::{Limits, Snake, common_prefix, common_suffix, diff_parts, expired, push};
+
+// finds the shortest edit script between two token sequences
+// outputs the runs of tokens both inputs have in common, in order
+//
+// This algorithm is based on
+// Eugene W. Myers, "An O(ND) Difference Algorithm and Its Variations" (1986).
+// Only the part of every `V` array that was reached is kept for the
+// backtrack, so memory grows with the square of the edit distance instead
+// of with the product of the input lengths.
+//
+// Once `deadline` has passed, no common tokens are reported at all.
+#[expect(clippy::many_single_char_names)] // Names follow the paper
+pub fn diff<T: PartialEq>(orig: &[T], edit: &[T], deadline: Option<Instant>) -> Vec<Snake> {
+    let n = orig.len();
+    let m = edit.len();
+    let max = n + m;
+    // diagonal `k = x - y` is stored at `k + offset`, keeping every index
+    // touched below (including `k - 1` and `k + 1`) in bounds
+    let offset = max + 1;
+    let mut v = vec![0; 2 * offset + 1];
+    let mut trace: Vec<Vec<usize>> = Vec::new();
+
+    for d in 0..=max {
+        if expired(deadline) {
+            return Vec::new();
+        }
+        for k in (offset - d..=offset + d).step_by(2) {
+            let mut x = if k == offset - d || (k != offset + d && v[k - 1] < v[k + 1]) {
+                v[k + 1]
+            } else {
+                v[k - 1] + 1
+            };
+            let mut y = x + offset - k;
+            let len = common_prefix(
+                orig.get(x..).unwrap_or_default(),
+                edit.get(y..).unwrap_or_default(),
+            );
+            x += len;
+            y += len;
+            v[k] = x;
+            if x >= n && y >= m {
+                return backtrack(&trace, offset, n, m);
+            }
+        }
+        trace.push(v[offset - d..=offset + d].to_vec());
+    }
+
+    unreachable!("the edit distance is bounded by the sum of the input lengths")
+}
+
+// walks the saved `V` arrays back from `(n, m)` to `(0, 0)`, collecting the
+// snake that follows every edit
+#[expect(clippy::many_single_char_names)] // Names follow the paper
+fn backtrack(trace: &[Vec<usize>], offset: usize, n: usize, m: usize) -> Vec<Snake> {
+    let mut snakes = Vec::new();
+    let mut x = n;
+    let mut y = m;
+
+    for d in (1..=trace.len()).rev() {
+        let prev = &trace[d - 1];
+        let first = offset + 1 - d;
+        let k = x + offset - y;
+        let down =
+            k == offset - d || (k != offset + d && prev[k - 1 - first] < prev[k + 1 - first]);
+        let prev_k = if down { k + 1 } else { k - 1 };
+        let prev_x = prev[prev_k - first];
+        let prev_y = prev_x + offset - prev_k;
+        let (start_x, start_y) = if down {
+            (prev_x, prev_y + 1)
+        } else {
+            (prev_x + 1, prev_y)
+        };
+        if x > start_x {
+            snakes.push(Snake {
+                a: start_x,
+                b: start_y,
+                len: x - start_x,
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    if x > 0 {
+        snakes.push(Snake { a: 0, b: 0, len: x });
+    }
+
+    snakes.reverse();
+    snakes
+}
+
+// finds the shortest edit script between two token sequences like `diff`,
+// but in linear space
+//
+// Instead of keeping every `V` array, the edit graph is searched from both
+// ends at once until the paths meet in a "middle snake". The inputs are
+// split there and both halves are solved recursively, see section 4b of
+// Myers' paper.
+//
+// Once the deadline of `limits` has passed, the parts that were not split yet
+// are reported without any common tokens.
+pub fn diff_linear<T: PartialEq + Sync>(orig: &[T], edit: &[T], limits: Limits) -> Vec<Snake> {
+    let mut snakes = Vec::new();
+    bisect(orig, edit, 0, 0, limits, &mut snakes);
+    snakes
+}
+
+// appends the snakes of `orig` and `edit`, which start at `a` and `b` of the
+// whole inputs, to `snakes`
+fn bisect<T: PartialEq + Sync>(
+    orig: &[T],
+    edit: &[T],
+    a: usize,
+    b: usize,
+    limits: Limits,
+    snakes: &mut Vec<Snake>,
+) {
+    let prefix = common_prefix(orig, edit);
+    push(snakes, Snake { a, b, len: prefix });
+    let (orig, edit) = (&orig[prefix..], &edit[prefix..]);
+    let (a, b) = (a + prefix, b + prefix);
+
+    let suffix = common_suffix(orig, edit);
+    let (orig, edit) = (&orig[..orig.len() - suffix], &edit[..edit.len() - suffix]);
+
+    if !orig.is_empty()
+        && !edit.is_empty()
+        && let Some((x, y)) = middle_snake(orig, edit, limits.deadline)
+    {
+        let halves = [
+            (&orig[..x], &edit[..y], a, b),
+            (&orig[x..], &edit[y..], a + x, b + y),
+        ];
+        diff_parts(
+            &halves,
+            orig.len() + edit.len(),
+            limits,
+            snakes,
+            |&(orig, edit, a, b), snakes| bisect(orig, edit, a, b, limits, snakes),
+        );
+    }
+
+    push(
+        snakes,
+        Snake {
+            a: a + orig.len(),
+            b: b + edit.len(),
+            len: suffix,
+        },
+    );
+}
+
+// the snakes of `diff_linear`, found one part of the inputs at a time, in
+// order, so that the first ones are known before the rest is searched
+//
+// Snakes of neighbouring parts are not joined when they touch.
+pub struct Bisection<T> {
+    orig: Vec<T>,
+    edit: Vec<T>,
+    // the parts left to search and the snakes between them, the next one last
+    stack: Vec<Step>,
+}
+
+enum Step {
+    Search(Range<usize>, Range<usize>),
+    Found(Snake),
+}
+
+impl<T: PartialEq> Bisection<T> {
+    pub fn new(orig: Vec<T>, edit: Vec<T>) -> Bisection<T> {
+        let stack = vec![Step::Search(0..orig.len(), 0..edit.len())];
+        Bisection { orig, edit, stack }
+    }
+}
+
+impl<T: PartialEq> Iterator for Bisection<T> {
+    type Item = Snake;
+
+    fn next(&mut self) -> Option<Snake> {
+        while let Some(step) = self.stack.pop() {
+            let (range_a, range_b) = match step {
+                Step::Found(snake) if snake.len > 0 => return Some(snake),
+                Step::Found(_) => continue,
+                Step::Search(range_a, range_b) => (range_a, range_b),
+            };
+            let (orig, edit) = (&self.orig[range_a.clone()], &self.edit[range_b.clone()]);
+            let prefix = common_prefix(orig, edit);
+            let (orig, edit) = (&orig[prefix..], &edit[prefix..]);
+            let (a, b) = (range_a.start + prefix, range_b.start + prefix);
+            let suffix = common_suffix(orig, edit);
+            let (orig, edit) = (&orig[..orig.len() - suffix], &edit[..edit.len() - suffix]);
+
+            self.stack.push(Step::Found(Snake {
+                a: a + orig.len(),
+                b: b + edit.len(),
+                len: suffix,
+            }));
+            if !orig.is_empty()
+                && !edit.is_empty()
+                && let Some((x, y)) = middle_snake(orig, edit, None)
+            {
+                self.stack
+                    .push(Step::Search(a + x..a + orig.len(), b + y..b + edit.len()));
+                self.stack.push(Step::Search(a..a + x, b..b + y));
+            }
+            self.stack.push(Step::Found(Snake {
+                a: range_a.start,
+                b: range_b.start,
+                len: prefix,
+            }));
+        }
+        None
+    }
+}
+
+// finds the point where a forward and a reverse shortest path through the
+// edit graph meet, as the amount of tokens of `orig` and `edit` before it
+//
+// Both inputs must be non-empty and must neither start nor end with the
+// same token, which guarantees the point splits them into smaller problems.
+// Gives up once `deadline` has passed.
+fn middle_snake<T: PartialEq>(
+    orig: &[T],
+    edit: &[T],
+    deadline: Option<Instant>,
+) -> Option<(usize, usize)> {
+    let n = orig.len();
+    let m = edit.len();
+    let max = n + m;
+    // the forward diagonal `k = x - y` meets the reverse diagonal
+    // `(n - m) - k`, both are stored with an `offset` like in `diff`
+    let offset = max + 1;
+    let odd = max % 2 == 1;
+    let mut forward = vec![0; 2 * offset + 1];
+    let mut reverse = vec![0; 2 * offset + 1];
+
+    for d in 0..=max.div_ceil(2) {
+        if expired(deadline) {
+            return None;
+        }
+        for k in (offset - d..=offset + d).step_by(2) {
+            let mut x = if k == offset - d || (k != offset + d && forward[k - 1] < forward[k + 1]) {
+                forward[k + 1]
+            } else {
+                forward[k - 1] + 1
+            };
+            let mut y = x + offset - k;
+            if x > n || y > m {
+                forward[k] = x;
+                continue;
+            }
+            let len = common_prefix(&orig[x..], &edit[y..]);
+            x += len;
+            y += len;
+            forward[k] = x;
+
+            // the reverse paths are one step behind
+            if odd
+                && d > 0
+                && let Some(r) = (n + 2 * offset).checked_sub(m + k)
+                && (offset + 1 - d..offset + d).contains(&r)
+                && x + reverse[r] >= n
+            {
+                return Some((x, y));
+            }
+        }
+
+        for k in (offset - d..=offset + d).step_by(2) {
+            let mut x = if k == offset - d || (k != offset + d && reverse[k - 1] < reverse[k + 1]) {
+                reverse[k + 1]
+            } else {
+                reverse[k - 1] + 1
+            };
+            let y = x + offset - k;
+            if x > n || y > m {
+                reverse[k] = x;
+                continue;
+            }
+            x += common_suffix(&orig[..n - x], &edit[..m - y]);
+            reverse[k] = x;
+
+            if !odd
+                && let Some(f) = (n + 2 * offset).checked_sub(m + k)
+                && (offset - d..=offset + d).contains(&f)
+                && forward[f] + x >= n
+            {
+                return Some((forward[f], forward[f] + offset - f));
+            }
+        }
+    }
+
+    unreachable!("the forward and reverse paths always meet")
+}
+
+// finds the length of the shortest edit script between two token sequences,
+// if it is at most `max`
+//
+// Like in Ukkonen's banded algorithm, only the diagonals that up to `max`
+// edits can reach are searched, taking O((N + M) * max) time at most.
+#[expect(clippy::many_single_char_names)] // Names follow the paper
+pub fn distance_within<T: PartialEq>(orig: &[T], edit: &[T], max: usize) -> Option<usize> {
+    let prefix = common_prefix(orig, edit);
+    let (orig, edit) = (&orig[prefix..], &edit[prefix..]);
+    let suffix = common_suffix(orig, edit);
+    let (orig, edit) = (&orig[..orig.len() - suffix], &edit[..edit.len() - suffix]);
+
+    let n = orig.len();
+    let m = edit.len();
+    // every edit moves to a neighbouring diagonal, and the path has to end
+    // on the one of `(n, m)`
+    if n.abs_diff(m) > max {
+        return None;
+    }
+    let max = max.min(n + m);
+    let offset = max + 1;
+    let mut v = vec![0; 2 * offset + 1];
+
+    for d in 0..=max {
+        for k in (offset - d..=offset + d).step_by(2) {
+            let mut x = if k == offset - d || (k != offset + d && v[k - 1] < v[k + 1]) {
+                v[k + 1]
+            } else {
+                v[k - 1] + 1
+            };
+            let mut y = x + offset - k;
+            let len = common_prefix(
+                orig.get(x..).unwrap_or_default(),
+                edit.get(y..).unwrap_or_default(),
+            );
+            x += len;
+            y += len;
+            v[k] = x;
+            if x >= n && y >= m {
+                return Some(d);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+use crate::DiffOptions;
+
+#[test]
+fn test_myers() {
+    let a: Vec<char> = "abcabba".chars().collect();
+    let b: Vec<char> = "cbabac".chars().collect();
+    let common: usize = diff(&a, &b, None).iter().map(|s| s.len).sum();
+    assert_eq!(common, 4);
+
+    assert_eq!(
+        diff(&[1, 2, 3], &[1, 2, 3], None),
+        vec![Snake { a: 0, b: 0, len: 3 }]
+    );
+    assert_eq!(diff::<u8>(&[], &[1, 2], None), vec![]);
+    assert_eq!(diff(&[1, 2], &[3], None), vec![]);
+    assert_eq!(
+        diff(&[1, 2, 3, 4], &[1, 3, 4, 5], None),
+        vec![Snake { a: 0, b: 0, len: 1 }, Snake { a: 2, b: 1, len: 2 }]
+    );
+}
+
+#[test]
+fn test_myers_linear() {
+    let limits = Limits::from(&DiffOptions::new());
+    assert_eq!(
+        diff_linear(&[1, 2, 3], &[1, 2, 3], limits),
+        vec![Snake { a: 0, b: 0, len: 3 }]
+    );
+    assert_eq!(diff_linear::<u8>(&[], &[1, 2], limits), vec![]);
+    assert_eq!(diff_linear(&[1, 2], &[3], limits), vec![]);
+    assert_eq!(
+        diff_linear(&[1, 2, 3, 4], &[1, 3, 4, 5], limits),
+        vec![Snake { a: 0, b: 0, len: 1 }, Snake { a: 2, b: 1, len: 2 }]
+    );
+}
+
+#[test]
+fn test_myers_deadline() {
+    let deadline = Some(Instant::now());
+    assert_eq!(diff(&[1, 2, 3], &[1, 2, 4], deadline), vec![]);
+    // the linear variant still matches the common prefix and suffix
+    let limits = Limits {
+        deadline,
+        ..Limits::from(&DiffOptions::new())
+    };
+    assert_eq!(
+        diff_linear(&[1, 2, 3, 4], &[1, 5, 6, 4], limits),
+        vec![Snake { a: 0, b: 0, len: 1 }, Snake { a: 3, b: 3, len: 1 }]
+    );
+}
+
+#[test]
+fn test_distance_within() {
+    assert_eq!(distance_within(&[1, 2, 3], &[1, 2, 3], 0), Some(0));
+    assert_eq!(distance_within(&[1, 2, 3], &[1, 4, 3], 2), Some(2));
+    assert_eq!(distance_within(&[1, 2, 3], &[1, 4, 3], 1), None);
+    assert_eq!(distance_within(&[1, 2, 3, 4, 5], &[1], 3), None);
+    assert_eq!(distance_within::<u8>(&[], &[], 0), Some(0));
+}
+
+#[test]
+fn test_bisection() {
+    let limits = Limits::from(&DiffOptions::new());
+    for (orig, edit) in [
+        (vec![1, 2, 3, 4], vec![1, 3, 4, 5]),
+        (vec![1, 2, 1, 2, 1], vec![2, 1, 2]),
+        (vec![], vec![1]),
+    ] {
+        let mut snakes = Vec::new();
+        for snake in Bisection::new(orig.clone(), edit.clone()) {
+            push(&mut snakes, snake);
+        }
+        assert_eq!(snakes, diff_linear(&orig, &edit, limits));
+    }
+}
@@ -0,0 +1,644 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+#[cfg(feature = "unicode")]
+use crate::Normalization;
+use crate::tokenizer::{AnyLines, KeepSeparators, Tokens};
+#[cfg(feature = "unicode")]
+use crate::tokenizer::{Graphemes, UnicodeWords};
+#[cfg(feature = "regex")]
+use crate::tokenizer::{RegexSplit, RegexTokens};
+use crate::{Algorithm, LineEndings, Split, Tokenizer, Whitespace};
+
+/// The options used to calculate a `Changeset` with `Changeset::with_options`
+///
+/// # Examples
+///
+/// ```
+/// use difference_rs::{Algorithm, Changeset, DiffOptions};
+///
+/// let options = DiffOptions::new()
+///     .split("\n")
+///     .algorithm(Algorithm::Histogram);
+/// let changeset = Changeset::with_options("a\nb\nc", "a\nc", &options);
+///
+/// assert_eq!(changeset.distance, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    pub(crate) algorithm: Algorithm,
+    pub(crate) split: String,
+    // splits the input strings instead of `split`, if set
+    pub(crate) tokenizer: Option<Tokens>,
+    pub(crate) whitespace: Whitespace,
+    pub(crate) ignore_blank_lines: bool,
+    #[cfg(feature = "unicode")]
+    pub(crate) ignore_case: bool,
+    pub(crate) line_endings: LineEndings,
+    #[cfg(feature = "unicode")]
+    pub(crate) normalization: Normalization,
+    pub(crate) merge_short_sames: usize,
+    pub(crate) linear_space_threshold: usize,
+    pub(crate) deadline: Option<Deadline>,
+}
+
+// when a diff has to be done by, see `DiffOptions::deadline`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    At(Instant),
+    // measured from when the diff starts
+    After(Duration),
+}
+
+impl DiffOptions {
+    /// Creates the default options: Myers' algorithm on a char-level split.
+    #[must_use]
+    pub const fn new() -> DiffOptions {
+        DiffOptions {
+            algorithm: Algorithm::Myers,
+            split: String::new(),
+            tokenizer: None,
+            whitespace: Whitespace::Exact,
+            ignore_blank_lines: false,
+            #[cfg(feature = "unicode")]
+            ignore_case: false,
+            line_endings: LineEndings::Compare,
+            #[cfg(feature = "unicode")]
+            normalization: Normalization::None,
+            merge_short_sames: 0,
+            linear_space_threshold: 2048,
+            deadline: None,
+        }
+    }
+
+    /// Sets the `Algorithm` used to find the differences.
+    #[must_use]
+    pub const fn algorithm(mut self, algorithm: Algorithm) -> DiffOptions {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets how to split the input strings, like the `split` parameter of
+    /// `Changeset::new`.
+    ///
+    /// Common splits are `""` for char-level, `" "` for word-level and `"\n"` for line-level.
+    #[must_use]
+    pub fn split(mut self, split: &str) -> DiffOptions {
+        split.clone_into(&mut self.split);
+        self.tokenizer = None;
+        self.line_endings = LineEndings::Compare;
+        self
+    }
+
+    /// Splits the input strings with the given `Tokenizer`, and puts its
+    /// separator between the differences when displaying the changeset.
+    ///
+    /// Replaces any split set before.
+    #[must_use]
+    pub fn tokenizer<T: Tokenizer + 'static>(self, tokenizer: T) -> DiffOptions {
+        self.tokens(Tokens::Custom(Arc::new(tokenizer)))
+    }
+
+    // splits the input strings with `tokens`, like `tokenizer`
+    fn tokens(mut self, tokens: Tokens) -> DiffOptions {
+        tokens.separator().clone_into(&mut self.split);
+        self.tokenizer = Some(tokens);
+        self.line_endings = LineEndings::Compare;
+        self
+    }
+
+    /// Splits the input strings into grapheme clusters, which are the chars
+    /// as users perceive them. Like a char-level split, but an emoji with a
+    /// skin tone or a letter with combining accents is never cut in half.
+    ///
+    /// Replaces any split set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().graphemes();
+    /// let changeset = Changeset::with_options("cafe\u{301}", "cafe", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("caf".to_string()),
+    ///     Difference::Rem("e\u{301}".to_string()),
+    ///     Difference::Add("e".to_string()),
+    /// ]);
+    /// ```
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub fn graphemes(self) -> DiffOptions {
+        self.tokens(Tokens::Graphemes(Graphemes))
+    }
+
+    /// Splits the input strings at the word boundaries of Unicode, as in
+    /// [UAX #29](https://www.unicode.org/reports/tr29/#Word_Boundaries).
+    /// Unlike a word-level split on `" "`, punctuation and whitespace are
+    /// tokens of their own, so only the words that changed are reported.
+    ///
+    /// Replaces any split set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().words();
+    /// let changeset = Changeset::with_options("Roses are red,\tnot", "Roses are blue,\tnot", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("Roses are ".to_string()),
+    ///     Difference::Rem("red".to_string()),
+    ///     Difference::Add("blue".to_string()),
+    ///     Difference::Same(",\tnot".to_string()),
+    /// ]);
+    /// ```
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub fn words(self) -> DiffOptions {
+        self.tokens(Tokens::UnicodeWords(UnicodeWords))
+    }
+
+    /// Splits the input strings into the matches of a regular expression.
+    /// The text that doesn't match is not compared, but is still part of
+    /// the differences it is in the middle of.
+    ///
+    /// Replaces any split set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    /// use regex::Regex;
+    ///
+    /// let options = DiffOptions::new().regex(Regex::new(r"\w+|\s+|[^\w\s]").unwrap());
+    /// let changeset = Changeset::with_options("let x=foo(1);", "let x=bar(1);", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("let x=".to_string()),
+    ///     Difference::Rem("foo".to_string()),
+    ///     Difference::Add("bar".to_string()),
+    ///     Difference::Same("(1);".to_string()),
+    /// ]);
+    /// ```
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn regex(self, regex: Regex) -> DiffOptions {
+        self.tokens(Tokens::Regex(RegexTokens(regex)))
+    }
+
+    /// Splits the input strings by the matches of a regular expression, like
+    /// `DiffOptions::split` with a pattern instead of a literal split.
+    ///
+    /// Replaces any split set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    /// use regex::Regex;
+    ///
+    /// let options = DiffOptions::new().regex_split(Regex::new(r",\s*").unwrap());
+    /// let changeset = Changeset::with_options("a, b,c", "a,b, d", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("a, b".to_string()),
+    ///     Difference::Rem("c".to_string()),
+    ///     Difference::Add("d".to_string()),
+    /// ]);
+    /// ```
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn regex_split(self, regex: Regex) -> DiffOptions {
+        self.tokens(Tokens::RegexSplit(RegexSplit(regex)))
+    }
+
+    /// Splits the input strings into lines, which end at any of `"\r\n"`,
+    /// `"\n"` and `"\r"`. Unlike a line-level split on `"\n"`, every line
+    /// keeps its ending, so the differences make up the strings exactly.
+    ///
+    /// Whether lines that only differ in their endings are the same is set
+    /// by `endings`, until another split or tokenizer replaces the lines.
+    /// Replaces any split set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference, LineEndings};
+    ///
+    /// let (orig, edit) = ("a\r\nb\r\n", "a\nb\r\n");
+    ///
+    /// let options = DiffOptions::new().lines(LineEndings::Compare);
+    /// assert_eq!(Changeset::with_options(orig, edit, &options).diffs, vec![
+    ///     Difference::Rem("a\r\n".to_string()),
+    ///     Difference::Add("a\n".to_string()),
+    ///     Difference::Same("b\r\n".to_string()),
+    /// ]);
+    ///
+    /// let options = DiffOptions::new().lines(LineEndings::Ignore);
+    /// assert_eq!(Changeset::with_options(orig, edit, &options).diffs, vec![
+    ///     Difference::Same("a\r\nb\r\n".to_string()),
+    /// ]);
+    ///
+    /// let options = DiffOptions::new().lines(LineEndings::Report);
+    /// assert_eq!(Changeset::with_options(orig, edit, &options).diffs, vec![
+    ///     Difference::Same("a".to_string()),
+    ///     Difference::Rem("\r\n".to_string()),
+    ///     Difference::Add("\n".to_string()),
+    ///     Difference::Same("b\r\n".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn lines(self, endings: LineEndings) -> DiffOptions {
+        let mut options = self.tokens(Tokens::Lines(AnyLines {
+            separate_endings: endings == LineEndings::Report,
+        }));
+        options.line_endings = endings;
+        options
+    }
+
+    /// Keeps the text between the tokens of the split or tokenizer set before
+    /// as tokens of their own. Runs of separators and any leading or
+    /// trailing ones are compared and reported like any other text, so the
+    /// `Same` and `Rem` differences make up exactly the original string, and
+    /// the `Same` and `Add` ones the edited string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split(" ").keep_separators();
+    /// let changeset = Changeset::with_options("a  b c ", "a b c", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("a".to_string()),
+    ///     Difference::Rem("  ".to_string()),
+    ///     Difference::Add(" ".to_string()),
+    ///     Difference::Same("b c".to_string()),
+    ///     Difference::Rem(" ".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn keep_separators(mut self) -> DiffOptions {
+        let tokenizer = self
+            .tokenizer
+            .take()
+            .unwrap_or_else(|| Tokens::Split(Split(self.split.clone())));
+        let endings = self.line_endings;
+        let mut options = self.tokens(Tokens::KeepSeparators(KeepSeparators(Box::new(tokenizer))));
+        options.line_endings = endings;
+        options
+    }
+
+    /// Sets how whitespace is compared. Tokens that only differ in the
+    /// ignored whitespace are the same, and reported with the text of the
+    /// original string.
+    ///
+    /// Defaults to `Whitespace::Exact`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference, Whitespace};
+    ///
+    /// let options = DiffOptions::new().split("\n").whitespace(Whitespace::IgnoreAmount);
+    /// let changeset = Changeset::with_options("if a {\n  b();\n}", "if a  {\n\tb(); \n}", &options);
+    ///
+    /// assert_eq!(changeset.distance, 0);
+    /// assert_eq!(changeset.diffs, vec![Difference::Same("if a {\n  b();\n}".to_string())]);
+    /// ```
+    #[must_use]
+    pub const fn whitespace(mut self, whitespace: Whitespace) -> DiffOptions {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Sets whether to ignore changes that only add or remove blank tokens,
+    /// which are empty or only whitespace, like `diff --ignore-blank-lines`
+    /// does for lines. Such changes are reported as the same text, while
+    /// blank tokens next to other changes are still added or removed.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split("\n").ignore_blank_lines(true);
+    /// let changeset = Changeset::with_options("a\nb\nc", "a\n\nb\nc\n\n", &options);
+    ///
+    /// assert_eq!(changeset.distance, 0);
+    /// assert_eq!(changeset.diffs, vec![Difference::Same("a\nb\nc".to_string())]);
+    ///
+    /// let changeset = Changeset::with_options("a\nb\nc", "a\n\nb\nd\n", &options);
+    ///
+    /// assert_eq!(changeset.distance, 3);
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("a\nb".to_string()),
+    ///     Difference::Rem("c".to_string()),
+    ///     Difference::Add("d\n".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub const fn ignore_blank_lines(mut self, ignore: bool) -> DiffOptions {
+        self.ignore_blank_lines = ignore;
+        self
+    }
+
+    /// Sets whether to compare tokens with full Unicode case folding, so
+    /// that tokens that only differ in case are the same. They are reported
+    /// with the casing of the original string.
+    ///
+    /// Tokens are folded one at a time, so a char-level split can't find
+    /// "ß" and "SS" to be the same, since they are one and two chars. Split
+    /// the strings into words to compare them as a whole.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split(" ").ignore_case(true);
+    /// let changeset = Changeset::with_options("SELECT name FROM Straße", "select name from STRASSE", &options);
+    ///
+    /// assert_eq!(changeset.distance, 0);
+    /// assert_eq!(changeset.diffs, vec![Difference::Same("SELECT name FROM Straße".to_string())]);
+    /// ```
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub const fn ignore_case(mut self, ignore: bool) -> DiffOptions {
+        self.ignore_case = ignore;
+        self
+    }
+
+    /// Sets the Unicode normalization form tokens are compared in, so that
+    /// tokens that only differ in how their chars are encoded are the same.
+    /// They are reported with the text of the original string.
+    ///
+    /// A combining accent is a char of its own, so with the char-level split
+    /// `""`, the strings are split into grapheme clusters instead, like with
+    /// `DiffOptions::graphemes`, to compare the accented letters as a whole.
+    ///
+    /// Defaults to `Normalization::None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference, Normalization};
+    ///
+    /// let options = DiffOptions::new().normalization(Normalization::Nfc);
+    /// let changeset = Changeset::with_options("caf\u{e9}s", "cafe\u{301}", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("caf\u{e9}".to_string()),
+    ///     Difference::Rem("s".to_string()),
+    /// ]);
+    /// ```
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub const fn normalization(mut self, form: Normalization) -> DiffOptions {
+        self.normalization = form;
+        self
+    }
+
+    /// Sets the length of the runs of same tokens between two changes that
+    /// are merged into them, like the cleanup of short equalities in
+    /// diff-match-patch. The changeset has fewer, longer differences that
+    /// are easier to read, but a larger distance.
+    ///
+    /// Defaults to 0, which keeps every run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split(" ").merge_short_sames(1);
+    /// let changeset = Changeset::with_options("a b c d", "x b y d", &options);
+    ///
+    /// assert_eq!(changeset.distance, 6);
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Rem("a b c".to_string()),
+    ///     Difference::Add("x b y".to_string()),
+    ///     Difference::Same("d".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub const fn merge_short_sames(mut self, tokens: usize) -> DiffOptions {
+        self.merge_short_sames = tokens;
+        self
+    }
+
+    /// Sets the amount of tokens, counting both inputs, above which Myers'
+    /// algorithm switches to its linear space variant. The default variant is
+    /// faster, but may need memory quadratic in the size of the inputs.
+    ///
+    /// Defaults to 2048.
+    #[must_use]
+    pub const fn linear_space_threshold(mut self, tokens: usize) -> DiffOptions {
+        self.linear_space_threshold = tokens;
+        self
+    }
+
+    /// Sets an instant by which the diff has to be done. Once it has passed,
+    /// the parts of the inputs that were not compared yet are reported as
+    /// removed and added, so the changeset is still valid but may not be
+    /// minimal.
+    ///
+    /// Replaces any timeout set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use difference_rs::{Changeset, DiffOptions, Difference};
+    ///
+    /// let options = DiffOptions::new().split(" ").deadline(Instant::now());
+    /// let changeset = Changeset::with_options("a b c d", "a x c y", &options);
+    ///
+    /// assert_eq!(changeset.diffs, vec![
+    ///     Difference::Same("a".to_string()),
+    ///     Difference::Rem("b c d".to_string()),
+    ///     Difference::Add("x c y".to_string()),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Instant) -> DiffOptions {
+        self.deadline = Some(Deadline::At(deadline));
+        self
+    }
+
+    /// Sets how long every diff may take, like `DiffOptions::deadline` for
+    /// an instant `timeout` after the diff starts.
+    ///
+    /// Replaces any deadline set before.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> DiffOptions {
+        self.deadline = Some(Deadline::After(timeout));
+        self
+    }
+}
+
+// Custom tokenizers can't be compared, so options with one are only equal
+// if they use the same one, shared by cloning them.
+impl PartialEq for DiffOptions {
+    fn eq(&self, other: &DiffOptions) -> bool {
+        #[cfg(feature = "unicode")]
+        if self.ignore_case != other.ignore_case || self.normalization != other.normalization {
+            return false;
+        }
+        self.algorithm == other.algorithm
+            && self.split == other.split
+            && self.tokenizer == other.tokenizer
+            && self.whitespace == other.whitespace
+            && self.ignore_blank_lines == other.ignore_blank_lines
+            && self.line_endings == other.line_endings
+            && self.merge_short_sames == other.merge_short_sames
+            && self.linear_space_threshold == other.linear_space_threshold
+            && self.deadline == other.deadline
+    }
+}
+
+impl Eq for DiffOptions {}
+
+impl Default for DiffOptions {
+    fn default() -> DiffOptions {
+        DiffOptions::new()
+    }
+}
+
+#[test]
+fn test_options() {
+    let options = DiffOptions::new()
+        .algorithm(Algorithm::Patience)
+        .split(" ")
+        .linear_space_threshold(0);
+
+    assert_eq!(options.algorithm, Algorithm::Patience);
+    assert_eq!(options.split, " ");
+    assert!(options.tokenizer.is_none());
+    assert_eq!(options.whitespace, Whitespace::Exact);
+    assert!(!options.ignore_blank_lines);
+    assert_eq!(options.line_endings, LineEndings::Compare);
+    assert_eq!(options.merge_short_sames, 0);
+    assert_eq!(options.linear_space_threshold, 0);
+    assert_eq!(options.deadline, None);
+
+    let now = Instant::now();
+    let options = options.timeout(Duration::from_secs(1)).deadline(now);
+    assert_eq!(options.deadline, Some(Deadline::At(now)));
+    assert_eq!(DiffOptions::default(), DiffOptions::new());
+
+    let options = options
+        .whitespace(Whitespace::IgnoreAll)
+        .ignore_blank_lines(true);
+    assert_eq!(options.whitespace, Whitespace::IgnoreAll);
+    assert!(options.ignore_blank_lines);
+    assert_ne!(options, options.clone().ignore_blank_lines(false));
+
+    let options = options.merge_short_sames(2);
+    assert_eq!(options.merge_short_sames, 2);
+    assert_ne!(options, options.clone().merge_short_sames(0));
+}
+
+#[test]
+fn test_options_tokenizer() {
+    let options = DiffOptions::new().tokenizer(crate::Lines);
+    assert_eq!(options.split, "\n");
+    assert!(options.tokenizer.is_some());
+    assert_eq!(options.clone(), options);
+    assert_ne!(options, DiffOptions::new().tokenizer(crate::Lines));
+    assert_ne!(options, DiffOptions::new().split("\n"));
+
+    let options = options.split(" ");
+    assert_eq!(options.split, " ");
+    assert!(options.tokenizer.is_none());
+
+    let options = options.lines(LineEndings::Ignore);
+    assert_eq!(
+        (options.split.as_str(), options.line_endings),
+        ("", LineEndings::Ignore)
+    );
+    assert!(options.tokenizer.is_some());
+    assert_ne!(options, options.clone().lines(LineEndings::Report));
+    assert_eq!(options, DiffOptions::new().lines(LineEndings::Ignore));
+
+    let lines = DiffOptions::new().lines(LineEndings::Ignore);
+    assert_eq!(lines.clone().split("\n").line_endings, LineEndings::Compare);
+    assert_eq!(
+        lines.clone().tokenizer(crate::Lines).line_endings,
+        LineEndings::Compare
+    );
+    assert_eq!(
+        lines.clone().keep_separators().line_endings,
+        LineEndings::Ignore
+    );
+    assert_eq!(
+        lines.lines(LineEndings::Report).line_endings,
+        LineEndings::Report
+    );
+
+    let options = DiffOptions::new().split(" ").keep_separators();
+    assert_eq!(options, DiffOptions::new().split(" ").keep_separators());
+    assert_ne!(options, DiffOptions::new().split("\n").keep_separators());
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_options_unicode() {
+    for options in [DiffOptions::new().graphemes(), DiffOptions::new().words()] {
+        let options = options.split(" ");
+        assert_eq!(options.split, " ");
+        assert!(options.tokenizer.is_none());
+    }
+    let options = DiffOptions::new().split(" ").graphemes();
+    assert_eq!(options.split, "");
+    assert!(options.tokenizer.is_some());
+    let lines = DiffOptions::new().lines(LineEndings::Ignore);
+    assert_eq!(lines.clone().graphemes().line_endings, LineEndings::Compare);
+    assert_eq!(lines.words().line_endings, LineEndings::Compare);
+
+    assert_eq!(options, DiffOptions::new().graphemes());
+    assert_ne!(options, DiffOptions::new().words());
+
+    assert!(!options.ignore_case);
+    let options = options.ignore_case(true);
+    assert!(options.ignore_case);
+    assert_ne!(options, options.clone().ignore_case(false));
+
+    assert_eq!(options.normalization, Normalization::None);
+    let options = options.normalization(Normalization::Nfkd);
+    assert_eq!(options.normalization, Normalization::Nfkd);
+    assert_ne!(options, options.clone().normalization(Normalization::Nfd));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_options_regex() {
+    let options = DiffOptions::new()
+        .split(" ")
+        .regex(Regex::new(r"\w+").unwrap());
+    assert_eq!(options.split, "");
+    assert!(options.tokenizer.is_some());
+
+    let options = options.regex_split(Regex::new(r"\s+").unwrap());
+    assert_eq!(options.split, "");
+    assert!(options.tokenizer.is_some());
+
+    let regex = |pattern| DiffOptions::new().regex(Regex::new(pattern).unwrap());
+    assert_eq!(regex(r"\w+"), regex(r"\w+"));
+    assert_ne!(regex(r"\w+"), regex(r"\w"));
+    assert_ne!(
+        regex(r"\s+"),
+        DiffOptions::new().regex_split(Regex::new(r"\s+").unwrap())
+    );
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::lcs::{Limits, Snake, common_prefix, common_suffix, diff_parts, expired, myers, push};
+
+// finds the runs of tokens two sequences have in common, anchoring on
+// tokens that occur exactly once in each of them
+//
+// This algorithm is based on Bram Cohen's patience diff, as described in
+// https://bramcohen.livejournal.com/73318.html
+// The longest increasing sequence of unique tokens is matched first, then
+// the gaps between them are diffed recursively. Gaps without any unique
+// token fall back to Myers' algorithm.
+pub fn diff<T: Eq + Hash + Sync>(orig: &[T], edit: &[T], limits: Limits) -> Vec<Snake> {
+    let mut snakes = Vec::new();
+    patience(orig, edit, 0, 0, limits, &mut snakes);
+    snakes
+}
+
+// appends the snakes of `orig` and `edit`, which start at `a` and `b` of the
+// whole inputs, to `snakes`
+fn patience<T: Eq + Hash + Sync>(
+    orig: &[T],
+    edit: &[T],
+    a: usize,
+    b: usize,
+    limits: Limits,
+    snakes: &mut Vec<Snake>,
+) {
+    if orig.is_empty() || edit.is_empty() || expired(limits.deadline) {
+        return;
+    }
+
+    let anchors = unique_anchors(orig, edit);
+    if anchors.is_empty() {
+        for snake in myers(orig, edit, limits) {
+            push(
+                snakes,
+                Snake {
+                    a: a + snake.a,
+                    b: b + snake.b,
+                    len: snake.len,
+                },
+            );
+        }
+        return;
+    }
+
+    // like git, the common tokens around every anchor are matched before
+    // recursing into the gaps between them, growing backwards from an
+    // anchor first and then forwards from the previous one
+    //
+    // Every gap is preceded and followed by the tokens matched around it.
+    let mut gaps = Vec::with_capacity(anchors.len() + 1);
+    let (mut gap_a, mut gap_b) = (0, 0);
+    for (x, y) in anchors {
+        let suffix = common_suffix(&orig[gap_a..x], &edit[gap_b..y]);
+        let (x, y) = (x - suffix, y - suffix);
+        let prefix = common_prefix(&orig[gap_a..x], &edit[gap_b..y]);
+        gaps.push((
+            Snake {
+                a: a + gap_a,
+                b: b + gap_b,
+                len: prefix,
+            },
+            gap_a + prefix..x,
+            gap_b + prefix..y,
+            Snake {
+                a: a + x,
+                b: b + y,
+                len: suffix + 1,
+            },
+        ));
+        (gap_a, gap_b) = (x + suffix + 1, y + suffix + 1);
+    }
+    let prefix = common_prefix(&orig[gap_a..], &edit[gap_b..]);
+    gaps.push((
+        Snake {
+            a: a + gap_a,
+            b: b + gap_b,
+            len: prefix,
+        },
+        gap_a + prefix..orig.len(),
+        gap_b + prefix..edit.len(),
+        Snake {
+            a: a + orig.len(),
+            b: b + edit.len(),
+            len: 0,
+        },
+    ));
+
+    diff_parts(
+        &gaps,
+        orig.len() + edit.len(),
+        limits,
+        snakes,
+        |(before, gap_a, gap_b, after), snakes| {
+            push(snakes, *before);
+            patience(
+                &orig[gap_a.clone()],
+                &edit[gap_b.clone()],
+                a + gap_a.start,
+                b + gap_b.start,
+                limits,
+                snakes,
+            );
+            push(snakes, *after);
+        },
+    );
+}
+
+// pairs up the tokens that occur exactly once in both sequences, keeping the
+// longest chain of pairs that is in order in both of them
+fn unique_anchors<T: Eq + Hash>(orig: &[T], edit: &[T]) -> Vec<(usize, usize)> {
+    // token -> (occurrences in orig, index in orig, occurrences in edit, index in edit)
+    let mut seen: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+    for (i, token) in orig.iter().enumerate() {
+        let entry = seen.entry(token).or_insert((0, i, 0, 0));
+        entry.0 += 1;
+    }
+    for (j, token) in edit.iter().enumerate() {
+        if let Some(entry) = seen.get_mut(token) {
+            entry.2 += 1;
+            entry.3 = j;
+        }
+    }
+
+    let pairs = orig
+        .iter()
+        .filter_map(|token| match seen[token] {
+            (1, i, 1, j) => Some((i, j)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    longest_increasing(&pairs)
+}
+
+// finds the longest subsequence of `pairs`, already sorted by their first
+// element, whose second elements are increasing
+//
+// This is the "patience sorting" the algorithm is named after: every pair is
+// placed on the leftmost pile whose top is greater than it, remembering the
+// top of the pile to its left as its predecessor.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut piles: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; pairs.len()];
+    for (idx, &(_, j)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&top| pairs[top].1 < j);
+        if pile > 0 {
+            predecessors[idx] = Some(piles[pile - 1]);
+        }
+        if pile == piles.len() {
+            piles.push(idx);
+        } else {
+            piles[pile] = idx;
+        }
+    }
+
+    let mut chain = Vec::with_capacity(piles.len());
+    let mut current = piles.last().copied();
+    while let Some(idx) = current {
+        chain.push(pairs[idx]);
+        current = predecessors[idx];
+    }
+    chain.reverse();
+    chain
+}
+
+#[cfg(test)]
+use crate::DiffOptions;
+
+#[test]
+fn test_patience() {
+    let limits = Limits::from(&DiffOptions::new());
+    let orig = ["fn a() {", "    x", "}", "", "fn b() {", "    y", "}"];
+    let edit = ["fn b() {", "    y", "}", "", "fn c() {", "    z", "}"];
+
+    // "}" and "" repeat, so only "fn b() {" and "    y" are anchors, and the
+    // "}" after them is matched while growing the anchors
+    assert_eq!(
+        diff(&orig, &edit, limits),
+        vec![Snake { a: 4, b: 0, len: 3 }]
+    );
+
+    assert_eq!(
+        longest_increasing(&[(0, 3), (1, 0), (2, 1), (3, 4), (4, 2)]),
+        vec![(1, 0), (2, 1), (4, 2)]
+    );
+    assert_eq!(diff::<u8>(&[], &[], limits), vec![]);
+    assert_eq!(
+        diff(&[1, 2, 1], &[1, 1], limits),
+        vec![Snake { a: 0, b: 0, len: 1 }, Snake { a: 2, b: 1, len: 1 }]
+    );
+}
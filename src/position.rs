@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use crate::{ChangesetRef, DiffOptions, DifferenceRef};
+
+/// A position in a string
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// The byte offset from the start of the string
+    pub offset: usize,
+    /// The line, starting at 1. Lines end at every `'\n'`.
+    pub line: usize,
+    /// The column in the line, starting at 1, counted in chars
+    pub column: usize,
+}
+
+/// A part of a string, from `start` up to, but not including, `end`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// Where the part starts
+    pub start: Position,
+    /// Where the part ends
+    pub end: Position,
+}
+
+impl Span {
+    /// The byte range of the part in its string.
+    #[must_use]
+    pub const fn bytes(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    /// Whether the part has no text, like the `orig` span of an addition.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start.offset == self.end.offset
+    }
+}
+
+/// A difference, along with where its text is in the original and in the
+/// edited string
+///
+/// The text of an addition is not in the original string, so its `orig`
+/// span is empty, at the position the text was added at. The same goes for
+/// the `edit` span of a removal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PositionedDifference<'a> {
+    /// The difference
+    pub diff: DifferenceRef<'a>,
+    /// Where the difference is in the original string
+    pub orig: Span,
+    /// Where the difference is in the edited string
+    pub edit: Span,
+}
+
+impl<'a> ChangesetRef<'a> {
+    /// Calculates the differences between two given strings like
+    /// `ChangesetRef::with_options`, along with where every one of them is
+    /// in the original and in the edited string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use difference_rs::{ChangesetRef, DiffOptions, DifferenceRef};
+    ///
+    /// let orig = "fn a() {}\nfn b() {}";
+    /// let edit = "fn a() {}\nfn c() {}";
+    /// let positions = ChangesetRef::positions(orig, edit, &DiffOptions::new().split("\n"));
+    ///
+    /// assert_eq!(positions[1].diff, DifferenceRef::Rem("fn b() {}"));
+    /// assert_eq!(positions[1].orig.bytes(), 10..19);
+    /// assert_eq!(positions[1].orig.start.line, 2);
+    /// assert_eq!(positions[2].edit.start.line, 2);
+    /// ```
+    #[must_use]
+    pub fn positions(
+        orig: &'a str,
+        edit: &'a str,
+        options: &DiffOptions,
+    ) -> Vec<PositionedDifference<'a>> {
+        let (located, _) = ChangesetRef::located(orig, edit, options);
+        let mut orig = Locator::new(orig);
+        let mut edit = Locator::new(edit);
+        located
+            .into_iter()
+            .map(|located| PositionedDifference {
+                diff: located.diff,
+                orig: orig.span(located.orig),
+                edit: edit.span(located.edit),
+            })
+            .collect()
+    }
+}
+
+// walks through a string, keeping track of lines and columns
+struct Locator<'a> {
+    text: &'a str,
+    at: Position,
+}
+
+impl<'a> Locator<'a> {
+    const fn new(text: &'a str) -> Locator<'a> {
+        Locator {
+            text,
+            at: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+        }
+    }
+
+    // the position of `offset`, which can't be before the last one
+    fn position(&mut self, offset: usize) -> Position {
+        for c in self.text[self.at.offset..offset].chars() {
+            if c == '\n' {
+                self.at.line += 1;
+                self.at.column = 1;
+            } else {
+                self.at.column += 1;
+            }
+        }
+        self.at.offset = offset;
+        self.at
+    }
+
+    // the span of the bytes `range`, which can't start before the last one
+    fn span(&mut self, range: Range<usize>) -> Span {
+        Span {
+            start: self.position(range.start),
+            end: self.position(range.end),
+        }
+    }
+}
+
+#[test]
+fn test_positions() {
+    let orig = "Roses are red,\nviolets are blue,\nI wrote this library.";
+    let edit = "Roses are red,\nI wrote this\ndocumentation.";
+    let positions = ChangesetRef::positions(orig, edit, &DiffOptions::new().split(" "));
+
+    assert_eq!(
+        positions
+            .iter()
+            .map(|p| (p.diff, p.orig.bytes(), p.edit.bytes()))
+            .collect::<Vec<_>>(),
+        vec![
+            (DifferenceRef::Same("Roses are"), 0..9, 0..9),
+            (
+                DifferenceRef::Rem("red,\nviolets are blue,\nI"),
+                10..34,
+                10..10
+            ),
+            (DifferenceRef::Add("red,\nI"), 35..35, 10..16),
+            (DifferenceRef::Same("wrote"), 35..40, 17..22),
+            (DifferenceRef::Rem("this library."), 41..54, 23..23),
+            (DifferenceRef::Add("this\ndocumentation."), 54..54, 23..42),
+        ]
+    );
+    for p in &positions {
+        match p.diff {
+            DifferenceRef::Same(x) | DifferenceRef::Rem(x) => assert_eq!(&orig[p.orig.bytes()], x),
+            DifferenceRef::Add(x) => assert!(p.orig.is_empty() && &edit[p.edit.bytes()] == x),
+        }
+    }
+
+    let rem = positions[1];
+    assert_eq!(
+        (rem.orig.start, rem.orig.end),
+        (
+            Position {
+                offset: 10,
+                line: 1,
+                column: 11
+            },
+            Position {
+                offset: 34,
+                line: 3,
+                column: 2
+            }
+        )
+    );
+    assert_eq!(positions[5].edit.end.line, 3);
+}
+
+#[test]
+fn test_positions_unicode() {
+    let orig = "añb\nçd";
+    let edit = "añb\nçe";
+    let positions = ChangesetRef::positions(orig, edit, &DiffOptions::new());
+
+    assert_eq!(positions[1].diff, DifferenceRef::Rem("d"));
+    assert_eq!(positions[1].orig.bytes(), 7..8);
+    assert_eq!(
+        (positions[1].orig.start.line, positions[1].orig.start.column),
+        (2, 2)
+    );
+    assert_eq!(positions[2].edit.bytes(), 7..8);
+}
+
+#[test]
+fn test_positions_whitespace() {
+    // the same lines have a different length in the edited string
+    let options = DiffOptions::new()
+        .split("\n")
+        .whitespace(crate::Whitespace::IgnoreAmount);
+    let positions = ChangesetRef::positions("a  b\nc\nd", "a b\nc\ne", &options);
+
+    assert_eq!(positions[0].diff, DifferenceRef::Same("a  b\nc"));
+    assert_eq!(
+        (positions[0].orig.bytes(), positions[0].edit.bytes()),
+        (0..6, 0..5)
+    );
+    assert_eq!(positions[2].edit.bytes(), 6..7);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_positions_regex() {
+    // the separators between the tokens are not part of any difference
+    let options = DiffOptions::new().regex_split(regex::Regex::new(r",\s*").unwrap());
+    let positions = ChangesetRef::positions("a, b, c", "a,b,  d", &options);
+
+    assert_eq!(
+        positions
+            .iter()
+            .map(|p| (p.diff, p.orig.bytes(), p.edit.bytes()))
+            .collect::<Vec<_>>(),
+        vec![
+            (DifferenceRef::Same("a, b"), 0..4, 0..3),
+            (DifferenceRef::Rem("c"), 6..7, 6..6),
+            (DifferenceRef::Add("d"), 7..7, 6..7),
+        ]
+    );
+}
@@ -0,0 +1,123 @@
+use std::hash::Hash;
+
+use crate::DiffOptions;
+use crate::lcs::{Snake, lcs};
+
+/// Defines the contents of the differences between two slices.
+///
+/// The elements are borrowed from the slices.
+/// Differences will be delivered in order of appearance in the original slice
+/// Sequences of the same kind will be grouped into one `SliceDifference`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum SliceDifference<'a, T> {
+    /// Sequences that are the same
+    Same(&'a [T]),
+    /// Sequences that are an addition (don't appear in the first slice)
+    Add(&'a [T]),
+    /// Sequences that are a removal (don't appear in the second slice)
+    Rem(&'a [T]),
+}
+
+impl<'a, T> SliceDifference<'a, T> {
+    /// The elements of the difference, whatever its kind.
+    #[must_use]
+    pub const fn as_slice(&self) -> &'a [T] {
+        match *self {
+            SliceDifference::Same(slice)
+            | SliceDifference::Add(slice)
+            | SliceDifference::Rem(slice) => slice,
+        }
+    }
+}
+
+/// Calculates the differences between two slices of any comparable elements,
+/// like `Changeset::new` does for the tokens of two strings.
+///
+/// # Examples
+///
+/// ```
+/// use difference_rs::{SliceDifference, diff_slices};
+///
+/// let diffs = diff_slices(&[1, 2, 3, 4], &[1, 3, 4, 5]);
+///
+/// assert_eq!(diffs, vec![
+///     SliceDifference::Same(&[1][..]),
+///     SliceDifference::Rem(&[2]),
+///     SliceDifference::Same(&[3, 4]),
+///     SliceDifference::Add(&[5]),
+/// ]);
+/// ```
+#[must_use]
+pub fn diff_slices<'a, T: Eq + Hash>(orig: &'a [T], edit: &'a [T]) -> Vec<SliceDifference<'a, T>> {
+    diff_slices_with_options(orig, edit, &DiffOptions::new())
+}
+
+/// Calculates the differences between two slices like `diff_slices`,
+/// configured by the given `DiffOptions`. The split of the options is not
+/// used, since the elements are compared as they are.
+#[must_use]
+pub fn diff_slices_with_options<'a, T: Eq + Hash>(
+    orig: &'a [T],
+    edit: &'a [T],
+    options: &DiffOptions,
+) -> Vec<SliceDifference<'a, T>> {
+    group(orig, edit, &lcs(orig, edit, options))
+}
+
+// groups two sequences into the parts that were removed, added or kept,
+// given the runs of elements they have in common
+pub fn group<'a, T>(orig: &'a [T], edit: &'a [T], common: &[Snake]) -> Vec<SliceDifference<'a, T>> {
+    let mut ret = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+    let end = Snake {
+        a: orig.len(),
+        b: edit.len(),
+        len: 0,
+    };
+
+    for snake in common.iter().chain([&end]) {
+        if i < snake.a {
+            ret.push(SliceDifference::Rem(&orig[i..snake.a]));
+        }
+        if j < snake.b {
+            ret.push(SliceDifference::Add(&edit[j..snake.b]));
+        }
+        if snake.len > 0 {
+            ret.push(SliceDifference::Same(&orig[snake.a..snake.a + snake.len]));
+        }
+        i = snake.a + snake.len;
+        j = snake.b + snake.len;
+    }
+
+    ret
+}
+
+#[test]
+fn test_diff_slices() {
+    assert_eq!(
+        diff_slices(&["a", "b", "c"], &["a", "c", "d"]),
+        vec![
+            SliceDifference::Same(&["a"][..]),
+            SliceDifference::Rem(&["b"]),
+            SliceDifference::Same(&["c"]),
+            SliceDifference::Add(&["d"]),
+        ]
+    );
+    assert_eq!(
+        diff_slices::<u64>(&[], &[7]),
+        vec![SliceDifference::Add(&[7][..])]
+    );
+    assert_eq!(diff_slices::<u64>(&[], &[]), vec![]);
+    assert_eq!(SliceDifference::Rem(&[1, 2][..]).as_slice(), &[1, 2]);
+
+    let options = DiffOptions::new().algorithm(crate::Algorithm::Patience);
+    assert_eq!(
+        diff_slices_with_options(&[1, 2, 3], &[3, 1, 2], &options),
+        vec![
+            SliceDifference::Add(&[3][..]),
+            SliceDifference::Same(&[1, 2]),
+            SliceDifference::Rem(&[3]),
+        ]
+    );
+}
@@ -0,0 +1,457 @@
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::lcs::strsplit;
+
+/// Splits strings into the tokens that are compared to find their differences
+///
+/// Set one with `DiffOptions::tokenizer`. Every token has to be a part of the
+/// string, and the tokens have to be in the order they appear in. The text
+/// between two tokens, if any, is not compared, but is part of the
+/// differences it is in the middle of.
+///
+/// # Panics
+///
+/// Diffing with a tokenizer panics if it returns a token that is not a part
+/// of the string, or that starts before the end of the token before it.
+///
+/// # Examples
+///
+/// ```
+/// use difference_rs::{Changeset, DiffOptions, Difference, Tokenizer};
+///
+/// // splits comma separated values, ignoring the spaces around them
+/// #[derive(Debug)]
+/// struct Values;
+///
+/// impl Tokenizer for Values {
+///     fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+///         text.split(',').map(str::trim).collect()
+///     }
+///
+///     fn separator(&self) -> &str {
+///         ", "
+///     }
+/// }
+///
+/// let options = DiffOptions::new().tokenizer(Values);
+/// let changeset = Changeset::with_options("a, b,c", "a,b, d", &options);
+///
+/// assert_eq!(changeset.split, ", ");
+/// assert_eq!(changeset.diffs, vec![
+///     Difference::Same("a, b".to_string()),
+///     Difference::Rem("c".to_string()),
+///     Difference::Add("d".to_string()),
+/// ]);
+/// ```
+pub trait Tokenizer: fmt::Debug + Send + Sync {
+    /// Splits `text` into tokens, which are parts of it in order.
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str>;
+
+    /// The text between the tokens, which becomes the `split` of the
+    /// changeset and is put between its differences when displaying it.
+    ///
+    /// Defaults to `""`, for tokens that cover all of the text.
+    #[expect(clippy::unnecessary_literal_bound)] // Implementations may borrow it from themselves
+    fn separator(&self) -> &str {
+        ""
+    }
+}
+
+/// Splits strings into chars, the char-level split `""` of `Changeset::new`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Chars;
+
+/// Splits strings at every space, the word-level split `" "` of
+/// `Changeset::new`
+///
+/// Other whitespace, like tabs and newlines, is part of the tokens. See
+/// `DiffOptions::words` for a split at Unicode word boundaries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Spaces;
+
+/// Splits strings at every `'\n'`, the line-level split `"\n"` of
+/// `Changeset::new`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lines;
+
+/// Splits strings at every occurrence of a split, which is how the `split`
+/// parameter of `Changeset::new` and `DiffOptions::split` are tokenized
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Split(pub String);
+
+impl Tokenizer for Chars {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        strsplit(text, "")
+    }
+}
+
+impl Tokenizer for Spaces {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        strsplit(text, " ")
+    }
+
+    fn separator(&self) -> &'static str {
+        " "
+    }
+}
+
+impl Tokenizer for Lines {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        strsplit(text, "\n")
+    }
+
+    fn separator(&self) -> &'static str {
+        "\n"
+    }
+}
+
+impl Tokenizer for Split {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        strsplit(text, &self.0)
+    }
+
+    fn separator(&self) -> &str {
+        &self.0
+    }
+}
+
+// splits `text` with `tokenizer`, checking that its tokens are parts of
+// `text` in order, since the differences borrow the text between them
+pub fn checked<'a>(tokenizer: &dyn Tokenizer, text: &'a str) -> Vec<&'a str> {
+    let tokens = tokenizer.tokenize(text);
+    let mut end = 0;
+    for token in &tokens {
+        let start = token.as_ptr().addr().wrapping_sub(text.as_ptr().addr());
+        assert!(
+            end <= start && start <= text.len() && token.len() <= text.len() - start,
+            "{tokenizer:?} returned the token {token:?}, which is not a part of {text:?} after {:?}",
+            &text[..end],
+        );
+        end = start + token.len();
+    }
+    tokens
+}
+
+// splits strings into grapheme clusters, see `DiffOptions::graphemes`
+#[cfg(feature = "unicode")]
+#[derive(Clone, Debug)]
+pub struct Graphemes;
+
+#[cfg(feature = "unicode")]
+impl Tokenizer for Graphemes {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.graphemes(true).collect()
+    }
+}
+
+// splits strings at word boundaries, see `DiffOptions::words`
+#[cfg(feature = "unicode")]
+#[derive(Clone, Debug)]
+pub struct UnicodeWords;
+
+#[cfg(feature = "unicode")]
+impl Tokenizer for UnicodeWords {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        text.split_word_bounds().collect()
+    }
+}
+
+// splits strings into the matches of a pattern, see `DiffOptions::regex`
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct RegexTokens(pub Regex);
+
+#[cfg(feature = "regex")]
+impl Tokenizer for RegexTokens {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.0.find_iter(text).map(|m| m.as_str()).collect()
+    }
+}
+
+// splits strings by the matches of a pattern, see `DiffOptions::regex_split`
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct RegexSplit(pub Regex);
+
+#[cfg(feature = "regex")]
+impl Tokenizer for RegexSplit {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.0.split(text).collect()
+    }
+}
+
+// splits strings into lines, which end at any of "\r\n", "\n" and "\r",
+// see `DiffOptions::lines`
+#[derive(Clone, Debug)]
+pub struct AnyLines {
+    // whether line endings are tokens of their own, instead of part of the
+    // line they end
+    pub separate_endings: bool,
+}
+
+impl Tokenizer for AnyLines {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut tokens = Vec::new();
+        let mut rest = text;
+        while let Some(i) = rest.find(['\r', '\n']) {
+            let end = if rest[i..].starts_with("\r\n") {
+                i + 2
+            } else {
+                i + 1
+            };
+            if !self.separate_endings {
+                tokens.push(&rest[..end]);
+            } else if i > 0 {
+                tokens.extend([&rest[..i], &rest[i..end]]);
+            } else {
+                tokens.push(&rest[..end]);
+            }
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            tokens.push(rest);
+        }
+        tokens
+    }
+}
+
+// keeps the text between the tokens of another tokenizer as tokens of their
+// own, see `DiffOptions::keep_separators`
+#[derive(Clone, Debug)]
+pub struct KeepSeparators(pub Box<Tokens>);
+
+impl Tokenizer for KeepSeparators {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut tokens = Vec::new();
+        let mut end = 0;
+        for token in checked(self.0.as_ref(), text) {
+            if token.is_empty() {
+                continue;
+            }
+            let start = token.as_ptr().addr() - text.as_ptr().addr();
+            if end < start {
+                tokens.push(&text[end..start]);
+            }
+            tokens.push(token);
+            end = start + token.len();
+        }
+        if end < text.len() {
+            tokens.push(&text[end..]);
+        }
+        tokens
+    }
+}
+
+// the tokenizer of `DiffOptions`, where the built-in ones are compared by
+// their parameters, and custom ones only if they are the same
+#[derive(Clone)]
+pub enum Tokens {
+    Split(Split),
+    #[cfg(feature = "unicode")]
+    Graphemes(Graphemes),
+    #[cfg(feature = "unicode")]
+    UnicodeWords(UnicodeWords),
+    #[cfg(feature = "regex")]
+    Regex(RegexTokens),
+    #[cfg(feature = "regex")]
+    RegexSplit(RegexSplit),
+    Lines(AnyLines),
+    KeepSeparators(KeepSeparators),
+    Custom(Arc<dyn Tokenizer>),
+}
+
+impl Tokens {
+    fn tokenizer(&self) -> &dyn Tokenizer {
+        match self {
+            Tokens::Split(tokenizer) => tokenizer,
+            #[cfg(feature = "unicode")]
+            Tokens::Graphemes(tokenizer) => tokenizer,
+            #[cfg(feature = "unicode")]
+            Tokens::UnicodeWords(tokenizer) => tokenizer,
+            #[cfg(feature = "regex")]
+            Tokens::Regex(tokenizer) => tokenizer,
+            #[cfg(feature = "regex")]
+            Tokens::RegexSplit(tokenizer) => tokenizer,
+            Tokens::Lines(tokenizer) => tokenizer,
+            Tokens::KeepSeparators(tokenizer) => tokenizer,
+            Tokens::Custom(tokenizer) => tokenizer.as_ref(),
+        }
+    }
+}
+
+impl Tokenizer for Tokens {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.tokenizer().tokenize(text)
+    }
+
+    fn separator(&self) -> &str {
+        self.tokenizer().separator()
+    }
+}
+
+impl fmt::Debug for Tokens {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.tokenizer().fmt(f)
+    }
+}
+
+impl PartialEq for Tokens {
+    fn eq(&self, other: &Tokens) -> bool {
+        match (self, other) {
+            (Tokens::Split(a), Tokens::Split(b)) => a == b,
+            #[cfg(feature = "unicode")]
+            (Tokens::Graphemes(_), Tokens::Graphemes(_))
+            | (Tokens::UnicodeWords(_), Tokens::UnicodeWords(_)) => true,
+            #[cfg(feature = "regex")]
+            (Tokens::Regex(RegexTokens(a)), Tokens::Regex(RegexTokens(b)))
+            | (Tokens::RegexSplit(RegexSplit(a)), Tokens::RegexSplit(RegexSplit(b))) => {
+                a.as_str() == b.as_str()
+            }
+            (Tokens::Lines(a), Tokens::Lines(b)) => a.separate_endings == b.separate_endings,
+            (Tokens::KeepSeparators(a), Tokens::KeepSeparators(b)) => a.0 == b.0,
+            (Tokens::Custom(a), Tokens::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+#[test]
+fn test_tokenizers() {
+    let text = "a b\nc";
+    assert_eq!(Chars.tokenize(text), vec!["a", " ", "b", "\n", "c"]);
+    assert_eq!(Spaces.tokenize(text), vec!["a", "b\nc"]);
+    assert_eq!(Lines.tokenize(text), vec!["a b", "c"]);
+    assert_eq!(Split(" b".to_string()).tokenize(text), vec!["a", "\nc"]);
+    assert_eq!(
+        (Chars.separator(), Spaces.separator(), Lines.separator()),
+        ("", " ", "\n")
+    );
+
+    for (tokenizer, split) in [
+        (&Chars as &dyn Tokenizer, ""),
+        (&Spaces, " "),
+        (&Lines, "\n"),
+    ] {
+        let options = crate::DiffOptions::new().tokenizer(Split(split.to_string()));
+        assert_eq!(tokenizer.tokenize(text), strsplit(text, split));
+        assert_eq!(
+            crate::Changeset::with_options("a b\nc d", text, &options),
+            crate::Changeset::new("a b\nc d", text, split)
+        );
+    }
+    assert_eq!(
+        crate::Changeset::with_options("a\nb", "a\nc", &crate::DiffOptions::new().tokenizer(Lines)),
+        crate::Changeset::new("a\nb", "a\nc", "\n")
+    );
+}
+
+#[test]
+fn test_keep_separators() {
+    let tokenizer = KeepSeparators(Box::new(Tokens::Custom(Arc::new(Spaces))));
+    assert_eq!(
+        tokenizer.tokenize("  a  b\tc "),
+        vec!["  ", "a", "  ", "b\tc", " "]
+    );
+    assert!(tokenizer.tokenize("").is_empty());
+    assert_eq!(
+        KeepSeparators(Box::new(Tokens::Custom(Arc::new(Chars)))).tokenize("ab"),
+        vec!["a", "b"]
+    );
+
+    let orig = "fn  main() {\n\tprintln!();\n}\n";
+    let edit = "fn main() {\n    println!();\n}";
+    for options in [
+        crate::DiffOptions::new().split(" "),
+        crate::DiffOptions::new().split("\n"),
+        crate::DiffOptions::new().tokenizer(Lines),
+    ] {
+        let changeset = crate::Changeset::with_options(orig, edit, &options.keep_separators());
+        let (mut a, mut b) = (String::new(), String::new());
+        for diff in &changeset.diffs {
+            match diff {
+                crate::Difference::Same(x) => {
+                    a.push_str(x);
+                    b.push_str(x);
+                }
+                crate::Difference::Rem(x) => a.push_str(x),
+                crate::Difference::Add(x) => b.push_str(x),
+            }
+        }
+        assert_eq!((a.as_str(), b.as_str()), (orig, edit));
+        assert_eq!(changeset.split, "");
+    }
+}
+
+#[test]
+fn test_any_lines() {
+    let text = "a\r\nb\n\r\nc\rd\n";
+    assert_eq!(
+        AnyLines {
+            separate_endings: false
+        }
+        .tokenize(text),
+        vec!["a\r\n", "b\n", "\r\n", "c\r", "d\n"]
+    );
+    assert_eq!(
+        AnyLines {
+            separate_endings: true
+        }
+        .tokenize(text),
+        vec!["a", "\r\n", "b", "\n", "\r\n", "c", "\r", "d", "\n"]
+    );
+    assert_eq!(
+        AnyLines {
+            separate_endings: true
+        }
+        .tokenize("a\r\r\nb"),
+        vec!["a", "\r", "\r\n", "b"]
+    );
+    assert!(
+        AnyLines {
+            separate_endings: false
+        }
+        .tokenize("")
+        .is_empty()
+    );
+}
+
+#[derive(Debug)]
+#[cfg(test)]
+struct Misbehaving;
+
+#[cfg(test)]
+impl Tokenizer for Misbehaving {
+    fn tokenize<'a>(&self, _text: &'a str) -> Vec<&'a str> {
+        vec!["x"]
+    }
+}
+
+#[derive(Debug)]
+#[cfg(test)]
+struct Reversed;
+
+#[cfg(test)]
+impl Tokenizer for Reversed {
+    fn tokenize<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        Chars.tokenize(text).into_iter().rev().collect()
+    }
+}
+
+#[test]
+#[should_panic = r#"Misbehaving returned the token "x", which is not a part of "abc" after """#]
+fn test_misbehaving_tokenizer() {
+    let options = crate::DiffOptions::new().tokenizer(Misbehaving);
+    let _ = crate::Changeset::with_options("abc", "abd", &options);
+}
+
+#[test]
+#[should_panic = r#"Reversed returned the token "a", which is not a part of "ab" after "ab""#]
+fn test_tokens_out_of_order() {
+    let _ = KeepSeparators(Box::new(Tokens::Custom(Arc::new(Reversed)))).tokenize("ab");
+}
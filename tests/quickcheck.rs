@@ -1,5 +1,5 @@
 #![expect(missing_docs)]
-use difference_rs::{Changeset, Difference};
+use difference_rs::{Algorithm, Changeset, DiffOptions, Difference, Whitespace};
 use quickcheck::{QuickCheck, TestResult, quickcheck};
 use std::fmt;
 
@@ -43,6 +43,14 @@ impl<'a> Check<'a> {
         }
     }
 
+    fn with_options(old: &'a str, new: &'a str, options: &DiffOptions) -> Check<'a> {
+        Check {
+            old,
+            new,
+            changeset: Changeset::with_options(old, new, options),
+        }
+    }
+
     fn check(&self) -> TestResult {
         let split = self.changeset.split.as_str();
 
@@ -127,3 +135,265 @@ fn fuzzy() {
         .max_tests(10_000) // max attempts
         .quickcheck(prop as fn(Vec<usize>, Vec<usize>, Vec<char>) -> TestResult);
 }
+
+// the words of `input`, separated by spaces, from an alphabet of four words
+fn words(input: &[u8]) -> String {
+    input
+        .iter()
+        .map(|x| ["a", "b", "c", "d"][usize::from(x % 4)])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// `input` repeated up to `len` chars, from an alphabet of four chars
+fn chars(input: &[u8], len: usize) -> String {
+    input
+        .iter()
+        .cycle()
+        .take(len)
+        .map(|x| char::from(b'a' + x % 4))
+        .collect()
+}
+
+// the edit distance of a minimal changeset of two strings split by `" "`,
+// from the table of the longest common subsequence of every pair of prefixes
+//
+// An empty string has no tokens, rather than one empty token.
+fn lcs_distance(old: &str, new: &str) -> i128 {
+    fn tokens(s: &str) -> Vec<&str> {
+        if s.is_empty() {
+            Vec::new()
+        } else {
+            s.split(' ').collect()
+        }
+    }
+    let (old, new) = (tokens(old), tokens(new));
+    let mut row = vec![0; new.len() + 1];
+    for a in &old {
+        let mut diagonal = 0;
+        for (j, b) in new.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == b {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    (old.len() + new.len() - 2 * row[new.len()]) as i128
+}
+
+#[test]
+fn algorithms() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> TestResult {
+        let (old, new) = (words(&old), words(&new));
+        let minimal = lcs_distance(&old, &new);
+
+        for algorithm in [Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram] {
+            let options = DiffOptions::new().split(" ").algorithm(algorithm);
+            let check = Check::with_options(&old, &new, &options);
+            let result = check.check();
+            if result.is_failure() {
+                return result;
+            }
+            // only Myers' algorithm finds a minimal changeset
+            let distance = check.changeset.distance;
+            if distance < minimal || (algorithm == Algorithm::Myers && distance > minimal) {
+                return TestResult::error(format!(
+                    "{algorithm:?} distance is {distance}, not {minimal} in {check}"
+                ));
+            }
+        }
+        TestResult::passed()
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> TestResult);
+}
+
+#[test]
+fn linear_space() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> TestResult {
+        let (old, new) = (words(&old), words(&new));
+        let options = DiffOptions::new().split(" ").linear_space_threshold(0);
+        let check = Check::with_options(&old, &new, &options);
+        let result = check.check();
+        if result.is_failure() {
+            return result;
+        }
+        TestResult::from_bool(check.changeset.distance == lcs_distance(&old, &new))
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> TestResult);
+}
+
+#[test]
+fn keep_separators() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> TestResult {
+        let text = |input: &[u8]| {
+            input
+                .iter()
+                .map(|x| ['a', 'b', ' ', '\n'][usize::from(x % 4)])
+                .collect::<String>()
+        };
+        let (old, new) = (text(&old), text(&new));
+
+        for options in [
+            DiffOptions::new().split(" "),
+            DiffOptions::new().split("\n"),
+        ] {
+            let check = Check::with_options(&old, &new, &options.keep_separators());
+            let result = check.check();
+            if result.is_failure() {
+                return result;
+            }
+        }
+        TestResult::passed()
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> TestResult);
+}
+
+#[test]
+fn whitespace() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> TestResult {
+        let text = |input: &[u8]| {
+            input
+                .iter()
+                .map(|x| ['a', 'b', ' ', '\t', '\n'][usize::from(x % 5)])
+                .collect::<String>()
+        };
+        let (old, new) = (text(&old), text(&new));
+
+        for split in [" ", "\n"] {
+            for whitespace in [
+                Whitespace::Exact,
+                Whitespace::IgnoreAmount,
+                Whitespace::IgnoreAll,
+                Whitespace::IgnoreTrailing,
+            ] {
+                let options = DiffOptions::new().split(split).whitespace(whitespace);
+                let check = Check::with_options(&old, &new, &options);
+                // the same text is reported as it is in the original string
+                let rebuilt = check
+                    .changeset
+                    .diffs
+                    .iter()
+                    .filter_map(|d| match d {
+                        Difference::Same(x) | Difference::Rem(x) => Some(x.as_str()),
+                        Difference::Add(_) => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(split);
+                if rebuilt != old {
+                    return TestResult::error(format!(
+                        "{whitespace:?} diff output implies old=`{rebuilt:?}`, not `{old:?}` in {check}"
+                    ));
+                }
+            }
+        }
+        TestResult::passed()
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> TestResult);
+}
+
+#[test]
+fn distance_zero() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> TestResult {
+        let text = |input: &[u8]| {
+            input
+                .iter()
+                .map(|x| ['a', ' ', '\t', '\n'][usize::from(x % 4)])
+                .collect::<String>()
+        };
+        let (old, new) = (text(&old), text(&new));
+
+        for split in [" ", "\n"] {
+            for whitespace in [
+                Whitespace::Exact,
+                Whitespace::IgnoreAmount,
+                Whitespace::IgnoreAll,
+                Whitespace::IgnoreTrailing,
+            ] {
+                for ignore_blank_lines in [false, true] {
+                    let options = DiffOptions::new()
+                        .split(split)
+                        .whitespace(whitespace)
+                        .ignore_blank_lines(ignore_blank_lines);
+                    let check = Check::with_options(&old, &new, &options);
+                    let same = check
+                        .changeset
+                        .diffs
+                        .iter()
+                        .all(|d| matches!(d, Difference::Same(_)));
+                    if same != (check.changeset.distance == 0) {
+                        return TestResult::error(format!(
+                            "distance is {} with {options:?} in {check}",
+                            check.changeset.distance
+                        ));
+                    }
+                }
+            }
+        }
+        TestResult::passed()
+    }
+
+    // most of the inputs that tell them apart have an empty side
+    QuickCheck::new()
+        .tests(1000)
+        .quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> TestResult);
+}
+
+#[test]
+fn distance_within() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> bool {
+        let (old, new) = (words(&old), words(&new));
+        let distance = Changeset::new(&old, &new, " ").distance;
+
+        Changeset::distance_within(&old, &new, " ", distance) == Some(distance)
+            && Changeset::distance_within(&old, &new, " ", distance + 5) == Some(distance)
+            && Changeset::distance_within(&old, &new, " ", distance - 1).is_none()
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> bool);
+}
+
+#[test]
+fn edit_distance() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> bool {
+        // up to a few words of 64 chars
+        let old = chars(&old, 3 * old.len());
+        let new = chars(&new, 3 * new.len());
+
+        Changeset::edit_distance(&old, &new, "") == Changeset::new(&old, &new, "").distance
+    }
+
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> bool);
+}
+
+#[test]
+fn iter_diff() {
+    #[expect(clippy::needless_pass_by_value)]
+    fn prop(old: Vec<u8>, new: Vec<u8>) -> bool {
+        let same = |old: &str, new: &str, split| {
+            Changeset::iter_diff(old, new, split).collect::<Vec<_>>()
+                == Changeset::new(old, new, split).diffs
+        };
+
+        // the second pair is large enough for the linear space variant of
+        // Myers' algorithm
+        same(&words(&old), &words(&new), " ") && same(&chars(&old, 2000), &chars(&new, 1500), "")
+    }
+
+    QuickCheck::new()
+        .tests(20)
+        .quickcheck(prop as fn(Vec<u8>, Vec<u8>) -> bool);
+}